@@ -1,15 +1,43 @@
 // ChainProof Protocol v2 - Complete Anchor Program
 // Token Registry + Reward Pool + Staking + User Profiles + Developer Tracking
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, MintTo, Token, TokenAccount, Transfer};
 
 declare_id!("45gVbLLSYYcW254TFoJMXmfupM5dJaFxTLsbny2eqKWx");
 
 // Stake token mint: 2FKjWV4zh7AVsmXonL7AM9Lh9zfpcE3e1dCYejWvd5W8
-const STAKE_TOKEN_MINT: &str = "2FKjWV4zh7AVsmXonL7AM9Lh9zfpcE3e1dCYejWvd5W8";
-const VERIFICATION_THRESHOLD: u64 = 10; // 10 stakes for verified badge
-const UNSTAKE_COOLDOWN: i64 = 172800; // 48 hours in seconds
+const STAKE_TOKEN_MINT: Pubkey = pubkey!("2FKjWV4zh7AVsmXonL7AM9Lh9zfpcE3e1dCYejWvd5W8");
+const REWARD_PRECISION: u128 = 1_000_000_000_000; // fixed-point scale for reward_per_share
+const MAX_REWARD_STREAMS: usize = 4; // project-sponsored reward mints per project
+const ACTIVE_HISTORY_LEN: usize = 8; // active-amount checkpoints kept per position for voting snapshots
+const MAX_STAKE_BATCH_ENTRIES: usize = 8; // projects per stake_batch call
+const STAKE_BATCH_ACCOUNTS: usize = 4; // remaining accounts per stake_batch entry
+const MAX_RISK_SCORE: u16 = 100; // attestation risk scores run 0 (safest) to 100
+const MAX_ATTESTATION_TTL: i64 = 2592000; // attestations are valid for at most 30 days
+const DEFAULT_MAX_VERIFIED_RISK_SCORE: u16 = 65; // anything riskier than MODERATE can't be verified
+const DEFAULT_UNSTAKE_COOLDOWN: i64 = 172800; // 48 hours in seconds
+const MAX_UNSTAKE_COOLDOWN: i64 = 2592000; // cooldown can't be raised above 30 days
 const DEVELOPER_REFERRAL_CODE: &str = "CHAINPROOFDEV";
+const DEFAULT_ADMIN_TIMELOCK: i64 = 172800; // 48 hours before queued admin actions can execute
+const MIN_ADMIN_TIMELOCK: i64 = 86400; // the timelock itself can't drop below 24 hours
+const ADMIN_ACTION_GRACE_PERIOD: i64 = 1209600; // queued actions expire 14 days after their ETA
+const MIN_PROPOSAL_STAKE: u64 = 1_000_000_000; // 1,000 tokens (6 decimals) staked to propose
+const GOVERNANCE_VOTING_PERIOD: i64 = 432000; // 5 days in seconds
+const GOVERNANCE_QUORUM: u64 = 10_000_000_000; // 10,000 tokens of stake must vote
+const GOVERNANCE_APPROVAL_BPS: u64 = 5000; // more than 50% of votes cast must approve
+const MAX_ROLE_MEMBERS: usize = 16; // wallets holding at least one protocol role
+const AUTHORITY_INDEX_PAGE_SIZE: usize = 64; // mints per AuthorityIndex page
+const SYMBOL_INDEX_PAGE_SIZE: usize = 64; // mints per SymbolIndexPage
+const SCAM_REPORT_BOND: u64 = 1_000_000; // 1 token (6 decimals) posted by the reporter
+const SCAM_REPORT_BOUNTY: u64 = 5_000_000; // 5 tokens paid from the reward pool when upheld
+const SCAM_REPORT_REWARD_POINTS: u64 = 1_000; // reward points for an upheld report
+const DEFAULT_SLASH_RATE_BPS: u16 = 5000; // half of every stake on a confirmed rug
+const DEFAULT_CHALLENGE_DISPUTE_WINDOW: i64 = 604800; // challenges are locked for 7 days
+const MAX_CHALLENGE_DISPUTE_WINDOW: i64 = 2592000; // dispute window can't exceed 30 days
+const DEFAULT_CHALLENGE_PENALTY_BPS: u16 = 1000; // 10% of a failed challenge goes to backers
+const DEFAULT_CHALLENGE_MIN_AGE: i64 = 86400; // challenges must predate the flag by 24 hours to share a slash
+const DEFAULT_EMERGENCY_UNSTAKE_PENALTY_BPS: u16 = 1000; // 10% of an early withdrawal goes to the pool
 
 #[program]
 pub mod chainproof_protocol {
@@ -32,18 +60,54 @@ pub mod chainproof_protocol {
         require!(symbol.len() <= 10, ChainProofError::SymbolTooLong);
         require!(ipfs_hash.len() <= 100, ChainProofError::IpfsHashTooLong);
 
+        let normalized_symbol = normalize_symbol(&symbol);
+        require!(!normalized_symbol.is_empty(), ChainProofError::InvalidSymbol);
+
         token_entry.authority = ctx.accounts.authority.key();
         token_entry.mint = ctx.accounts.mint.key();
         token_entry.name = name;
         token_entry.symbol = symbol;
         token_entry.ipfs_hash = ipfs_hash;
         token_entry.timestamp = clock.unix_timestamp;
+        token_entry.listing_status = ListingStatus::Pending;
+        token_entry.listing_reason = ListingReason::None;
+        token_entry.reviewed_at = 0;
         token_entry.bump = ctx.bumps.token_entry;
 
+        // Index the mint under its normalized symbol
+        let symbol_index = &mut ctx.accounts.symbol_index;
+        init_symbol_index(symbol_index, &token_entry.symbol, ctx.bumps.symbol_index);
+
+        // Flag the entry if a verified project already owns this symbol
+        token_entry.impersonation_risk = symbol_index.verified_mint.is_some();
+
+        let symbol_page = add_to_symbol_index(
+            symbol_index,
+            &mut ctx.accounts.symbol_index_page,
+            ctx.bumps.symbol_index_page,
+            token_entry.mint,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        token_entry.symbol_index_page = Some(symbol_page);
+
+        let authority_index = &mut ctx.accounts.authority_index;
+        init_authority_index(authority_index, token_entry.authority, ctx.bumps.authority_index);
+        let authority_page = add_to_authority_index(
+            authority_index,
+            &mut ctx.accounts.authority_index_page,
+            ctx.bumps.authority_index_page,
+            token_entry.mint,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        token_entry.authority_index_page = Some(authority_page);
+
         emit!(TokenRegistered {
             mint: token_entry.mint,
             authority: token_entry.authority,
             name: token_entry.name.clone(),
+            impersonation_risk: token_entry.impersonation_risk,
             timestamp: token_entry.timestamp,
         });
 
@@ -63,11 +127,23 @@ pub mod chainproof_protocol {
         require!(symbol.len() <= 10, ChainProofError::SymbolTooLong);
         require!(ipfs_hash.len() <= 100, ChainProofError::IpfsHashTooLong);
 
+        // Cosmetic edits are fine here; a change that moves the entry to another
+        // symbol index needs the index accounts and goes through change_token_symbol
+        require!(
+            normalize_symbol(&symbol) == normalize_symbol(&token_entry.symbol),
+            ChainProofError::SymbolChangeNotAllowed
+        );
+
         token_entry.name = name;
         token_entry.symbol = symbol;
         token_entry.ipfs_hash = ipfs_hash;
         token_entry.timestamp = clock.unix_timestamp;
 
+        // Edited metadata goes back to curators; suspensions stay in place
+        if token_entry.listing_status != ListingStatus::Suspended {
+            token_entry.listing_status = ListingStatus::Pending;
+        }
+
         emit!(TokenUpdated {
             mint: token_entry.mint,
             authority: token_entry.authority,
@@ -78,6 +154,592 @@ pub mod chainproof_protocol {
         Ok(())
     }
 
+    pub fn transfer_token_authority(ctx: Context<TransferTokenAuthority>) -> Result<()> {
+        let token_entry = &mut ctx.accounts.token_entry;
+        let new_authority = ctx.accounts.new_authority.key();
+
+        require!(
+            new_authority != token_entry.authority,
+            ChainProofError::InvalidAuthority
+        );
+
+        let from_page = token_entry.authority_index_page;
+        remove_from_authority_index(
+            ctx.accounts.current_authority_index_page.as_deref_mut(),
+            token_entry,
+        )?;
+
+        let new_authority_index = &mut ctx.accounts.new_authority_index;
+        init_authority_index(new_authority_index, new_authority, ctx.bumps.new_authority_index);
+        let to_page = add_to_authority_index(
+            new_authority_index,
+            &mut ctx.accounts.new_authority_index_page,
+            ctx.bumps.new_authority_index_page,
+            token_entry.mint,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let previous_authority = token_entry.authority;
+        token_entry.authority = new_authority;
+        token_entry.authority_index_page = Some(to_page);
+
+        emit!(TokenAuthorityTransferred {
+            mint: token_entry.mint,
+            previous_authority,
+            new_authority,
+            from_page,
+            to_page,
+        });
+
+        Ok(())
+    }
+
+    pub fn deregister_token(ctx: Context<DeregisterToken>) -> Result<()> {
+        let token_entry = &ctx.accounts.token_entry;
+        let clock = Clock::get()?;
+
+        remove_from_authority_index(ctx.accounts.authority_index_page.as_deref_mut(), token_entry)?;
+
+        remove_from_symbol_index(
+            &mut ctx.accounts.symbol_index,
+            ctx.accounts.symbol_index_page.as_deref_mut(),
+            token_entry,
+        )?;
+
+        emit!(TokenDeregistered {
+            mint: token_entry.mint,
+            authority: token_entry.authority,
+            authority_index_page: token_entry.authority_index_page,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_verified_symbol(ctx: Context<ClaimVerifiedSymbol>) -> Result<()> {
+        let token_entry = &ctx.accounts.token_entry;
+        let symbol_index = &mut ctx.accounts.symbol_index;
+        init_symbol_index(symbol_index, &token_entry.symbol, ctx.bumps.symbol_index);
+
+        require!(
+            ctx.accounts.project_stakes.verification_level != VerificationLevel::None,
+            ChainProofError::ProjectNotVerified
+        );
+        require!(
+            token_entry.listing_status == ListingStatus::Approved,
+            ChainProofError::ListingNotApproved
+        );
+        require!(!token_entry.impersonation_risk, ChainProofError::ImpersonationRisk);
+        require!(
+            symbol_index.verified_mint.is_none(),
+            ChainProofError::SymbolAlreadyClaimed
+        );
+
+        symbol_index.verified_mint = Some(token_entry.mint);
+
+        emit!(SymbolClaimed {
+            symbol: symbol_index.symbol.clone(),
+            mint: token_entry.mint,
+        });
+
+        Ok(())
+    }
+
+    /// Frees a claimed symbol once its holder is no longer verified or approved.
+    /// Permissionless so a lapsed claim can't keep blocking the symbol.
+    pub fn release_verified_symbol(ctx: Context<ReleaseVerifiedSymbol>) -> Result<()> {
+        let token_entry = &ctx.accounts.token_entry;
+        let symbol_index = &mut ctx.accounts.symbol_index;
+
+        require!(
+            ctx.accounts.project_stakes.verification_level == VerificationLevel::None
+                || token_entry.listing_status != ListingStatus::Approved,
+            ChainProofError::SymbolClaimStillValid
+        );
+
+        symbol_index.verified_mint = None;
+
+        emit!(SymbolReleased {
+            symbol: symbol_index.symbol.clone(),
+            mint: token_entry.mint,
+        });
+
+        Ok(())
+    }
+
+    /// Moves a token entry to a new symbol, re-indexing it under the new
+    /// normalized symbol and re-checking it for impersonation.
+    pub fn change_token_symbol(ctx: Context<ChangeTokenSymbol>, symbol: String) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(symbol.len() <= 10, ChainProofError::SymbolTooLong);
+        let normalized_symbol = normalize_symbol(&symbol);
+        require!(!normalized_symbol.is_empty(), ChainProofError::InvalidSymbol);
+        require!(
+            normalized_symbol != normalize_symbol(&ctx.accounts.token_entry.symbol),
+            ChainProofError::SymbolUnchanged
+        );
+
+        init_symbol_index(
+            &mut ctx.accounts.current_symbol_index,
+            &ctx.accounts.token_entry.symbol,
+            ctx.bumps.current_symbol_index,
+        );
+        remove_from_symbol_index(
+            &mut ctx.accounts.current_symbol_index,
+            ctx.accounts.current_symbol_index_page.as_deref_mut(),
+            &ctx.accounts.token_entry,
+        )?;
+
+        let new_symbol_index = &mut ctx.accounts.new_symbol_index;
+        init_symbol_index(new_symbol_index, &symbol, ctx.bumps.new_symbol_index);
+        let symbol_page = add_to_symbol_index(
+            new_symbol_index,
+            &mut ctx.accounts.new_symbol_index_page,
+            ctx.bumps.new_symbol_index_page,
+            ctx.accounts.token_entry.mint,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let token_entry = &mut ctx.accounts.token_entry;
+        let previous_symbol = std::mem::replace(&mut token_entry.symbol, symbol);
+        token_entry.symbol_index_page = Some(symbol_page);
+        token_entry.impersonation_risk = new_symbol_index.verified_mint.is_some();
+        token_entry.timestamp = clock.unix_timestamp;
+
+        // A new symbol is a metadata edit, so it goes back to curators as well
+        if token_entry.listing_status != ListingStatus::Suspended {
+            token_entry.listing_status = ListingStatus::Pending;
+        }
+
+        emit!(TokenSymbolChanged {
+            mint: token_entry.mint,
+            previous_symbol,
+            symbol: token_entry.symbol.clone(),
+            impersonation_risk: token_entry.impersonation_risk,
+            timestamp: token_entry.timestamp,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // LISTING CURATION
+    // ============================================
+
+    pub fn approve_listing(ctx: Context<ReviewListing>) -> Result<()> {
+        let token_entry = &mut ctx.accounts.token_entry;
+        let clock = Clock::get()?;
+
+        require!(
+            token_entry.listing_status != ListingStatus::Approved,
+            ChainProofError::ListingAlreadyApproved
+        );
+
+        token_entry.listing_status = ListingStatus::Approved;
+        token_entry.listing_reason = ListingReason::None;
+        token_entry.reviewed_at = clock.unix_timestamp;
+
+        emit!(ListingReviewed {
+            mint: token_entry.mint,
+            status: token_entry.listing_status,
+            reason: token_entry.listing_reason,
+            curator: ctx.accounts.curator.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn reject_listing(ctx: Context<ReviewListing>, reason: ListingReason) -> Result<()> {
+        let token_entry = &mut ctx.accounts.token_entry;
+        let clock = Clock::get()?;
+
+        require!(reason != ListingReason::None, ChainProofError::ListingReasonRequired);
+
+        // Rejecting an approved (or already suspended) listing suspends it
+        token_entry.listing_status = match token_entry.listing_status {
+            ListingStatus::Approved | ListingStatus::Suspended => ListingStatus::Suspended,
+            ListingStatus::Pending | ListingStatus::Rejected => ListingStatus::Rejected,
+        };
+        token_entry.listing_reason = reason;
+        token_entry.reviewed_at = clock.unix_timestamp;
+
+        emit!(ListingReviewed {
+            mint: token_entry.mint,
+            status: token_entry.listing_status,
+            reason: token_entry.listing_reason,
+            curator: ctx.accounts.curator.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // ROLES
+    // ============================================
+
+    pub fn initialize_roles(ctx: Context<InitializeRoles>) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+
+        // The reward pool authority bootstraps as the first super-admin
+        roles.members = vec![RoleMember {
+            member: ctx.accounts.authority.key(),
+            roles: Role::SuperAdmin.mask(),
+        }];
+        roles.bump = ctx.bumps.roles;
+
+        emit!(RolesInitialized {
+            super_admin: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn grant_role(ctx: Context<ManageRoles>, member: Pubkey, role: Role) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+
+        require!(!roles.has_role(&member, role), ChainProofError::RoleAlreadyGranted);
+
+        match roles.members.iter_mut().find(|m| m.member == member) {
+            Some(entry) => entry.roles |= role.mask(),
+            None => {
+                require!(roles.members.len() < MAX_ROLE_MEMBERS, ChainProofError::TooManyRoleMembers);
+                roles.members.push(RoleMember {
+                    member,
+                    roles: role.mask(),
+                });
+            }
+        }
+
+        emit!(RoleGranted {
+            member,
+            role,
+            granted_by: ctx.accounts.super_admin.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn revoke_role(ctx: Context<ManageRoles>, member: Pubkey, role: Role) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+
+        require!(roles.has_role(&member, role), ChainProofError::RoleNotGranted);
+
+        // Never leave the protocol without a super-admin
+        if role == Role::SuperAdmin {
+            let super_admins = roles
+                .members
+                .iter()
+                .filter(|m| m.roles & Role::SuperAdmin.mask() != 0)
+                .count();
+            require!(super_admins > 1, ChainProofError::LastSuperAdmin);
+        }
+
+        if let Some(position) = roles.members.iter().position(|m| m.member == member) {
+            roles.members[position].roles &= !role.mask();
+            if roles.members[position].roles == 0 {
+                roles.members.swap_remove(position);
+            }
+        }
+
+        emit!(RoleRevoked {
+            member,
+            role,
+            revoked_by: ctx.accounts.super_admin.key(),
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // PROTOCOL CONFIG
+    // ============================================
+
+    pub fn initialize_protocol_config(ctx: Context<InitializeProtocolConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+
+        config.paused = 0;
+        config.admin_timelock = DEFAULT_ADMIN_TIMELOCK;
+        config.next_action_id = 0;
+        config.next_proposal_id = 0;
+        config.verification_tiers = DEFAULT_VERIFICATION_TIERS;
+        config.max_verified_risk_score = DEFAULT_MAX_VERIFIED_RISK_SCORE;
+        config.unstake_cooldown = DEFAULT_UNSTAKE_COOLDOWN;
+        config.slash_rate_bps = DEFAULT_SLASH_RATE_BPS;
+        config.challenge_dispute_window = DEFAULT_CHALLENGE_DISPUTE_WINDOW;
+        config.challenge_penalty_bps = DEFAULT_CHALLENGE_PENALTY_BPS;
+        config.challenge_min_age = DEFAULT_CHALLENGE_MIN_AGE;
+        config.emergency_unstake_penalty_bps = DEFAULT_EMERGENCY_UNSTAKE_PENALTY_BPS;
+        config.slash_destination = SlashDestination::RewardPool;
+        config.bump = ctx.bumps.protocol_config;
+
+        emit!(ProtocolConfigInitialized {
+            authority: ctx.accounts.super_admin.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn set_pause(ctx: Context<SetPause>, subsystem: Subsystem, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+
+        if paused {
+            config.paused |= subsystem.mask();
+        } else {
+            config.paused &= !subsystem.mask();
+        }
+
+        emit!(PauseUpdated {
+            subsystem,
+            paused,
+            pauser: ctx.accounts.pauser.key(),
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // TIMELOCKED ADMIN ACTIONS
+    // ============================================
+
+    pub fn queue_admin_action(ctx: Context<QueueAdminAction>, change: ParameterChange) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        let action = &mut ctx.accounts.pending_action;
+        let clock = Clock::get()?;
+
+        change.validate()?;
+
+        action.action_id = config.next_action_id;
+        action.proposer = ctx.accounts.super_admin.key();
+        action.change = change;
+        action.queued_at = clock.unix_timestamp;
+        action.eta = clock.unix_timestamp.checked_add(config.admin_timelock).unwrap();
+        action.expires_at = action.eta.checked_add(ADMIN_ACTION_GRACE_PERIOD).unwrap();
+        action.bump = ctx.bumps.pending_action;
+
+        config.next_action_id = config.next_action_id.checked_add(1).unwrap();
+
+        emit!(AdminActionQueued {
+            action_id: action.action_id,
+            change: action.change.clone(),
+            proposer: action.proposer,
+            eta: action.eta,
+            expires_at: action.expires_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn execute_admin_action(ctx: Context<ExecuteAdminAction>) -> Result<()> {
+        let action = &ctx.accounts.pending_action;
+        let clock = Clock::get()?;
+
+        require!(clock.unix_timestamp >= action.eta, ChainProofError::TimelockNotElapsed);
+        require!(clock.unix_timestamp <= action.expires_at, ChainProofError::AdminActionExpired);
+
+        apply_parameter_change(
+            &action.change,
+            &mut ctx.accounts.reward_pool,
+            &mut ctx.accounts.protocol_config,
+            &mut ctx.accounts.developer_registry,
+        )?;
+
+        // Authority changes only stage the new authority, which still has to accept
+        match action.change {
+            ParameterChange::RewardPoolAuthority { new_authority } => {
+                emit!(AuthorityHandoverProposed {
+                    target: ctx.accounts.reward_pool.key(),
+                    current_authority: ctx.accounts.reward_pool.authority,
+                    pending_authority: new_authority,
+                });
+            }
+            ParameterChange::DeveloperRegistryAuthority { new_authority } => {
+                emit!(AuthorityHandoverProposed {
+                    target: ctx.accounts.developer_registry.key(),
+                    current_authority: ctx.accounts.developer_registry.authority,
+                    pending_authority: new_authority,
+                });
+            }
+            _ => {}
+        }
+
+        emit!(AdminActionExecuted {
+            action_id: action.action_id,
+            change: action.change.clone(),
+            executor: ctx.accounts.executor.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Super admins can cancel any queued action; once an action has expired
+    /// anyone can close it and return the rent to the proposer.
+    pub fn cancel_admin_action(ctx: Context<CancelAdminAction>) -> Result<()> {
+        let action = &ctx.accounts.pending_action;
+        let clock = Clock::get()?;
+
+        require!(
+            ctx.accounts.roles.has_role(&ctx.accounts.canceller.key(), Role::SuperAdmin)
+                || clock.unix_timestamp > action.expires_at,
+            ChainProofError::MissingRole
+        );
+
+        emit!(AdminActionCancelled {
+            action_id: action.action_id,
+            change: action.change.clone(),
+            cancelled_by: ctx.accounts.canceller.key(),
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // GOVERNANCE
+    // ============================================
+
+    /// The proposer's `UserStake` accounts are passed as remaining accounts, each
+    /// followed by the `ProjectStakes` of its project.
+    pub fn create_proposal(ctx: Context<CreateProposal>, change: ParameterChange) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        let proposal = &mut ctx.accounts.proposal;
+        let clock = Clock::get()?;
+
+        change.validate()?;
+        require!(!change.is_authority_change(), ChainProofError::InvalidParameter);
+
+        let stake = governance_weight(ctx.remaining_accounts, &ctx.accounts.proposer.key(), None)?;
+        require!(stake >= MIN_PROPOSAL_STAKE, ChainProofError::InsufficientProposalStake);
+
+        proposal.proposal_id = config.next_proposal_id;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.change = change;
+        proposal.created_at = clock.unix_timestamp;
+        proposal.voting_ends_at = clock.unix_timestamp.checked_add(GOVERNANCE_VOTING_PERIOD).unwrap();
+        proposal.votes_for = 0;
+        proposal.votes_against = 0;
+        proposal.status = ProposalStatus::Active;
+        proposal.bump = ctx.bumps.proposal;
+
+        config.next_proposal_id = config.next_proposal_id.checked_add(1).unwrap();
+
+        emit!(ProposalCreated {
+            proposal_id: proposal.proposal_id,
+            proposer: proposal.proposer,
+            change: proposal.change.clone(),
+            voting_ends_at: proposal.voting_ends_at,
+        });
+
+        Ok(())
+    }
+
+    /// The voter's `UserStake` accounts are passed as remaining accounts, each followed
+    /// by the `ProjectStakes` of its project. Votes weigh the active stake held when
+    /// the proposal was created.
+    pub fn cast_vote(ctx: Context<CastVote>, support: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let vote_record = &mut ctx.accounts.vote_record;
+        let clock = Clock::get()?;
+
+        require!(proposal.status == ProposalStatus::Active, ChainProofError::ProposalNotActive);
+        require!(clock.unix_timestamp < proposal.voting_ends_at, ChainProofError::VotingClosed);
+
+        let weight = governance_weight(
+            ctx.remaining_accounts,
+            &ctx.accounts.voter.key(),
+            Some(proposal.created_at),
+        )?;
+        require!(weight > 0, ChainProofError::NoVotingPower);
+
+        if support {
+            proposal.votes_for = proposal.votes_for.checked_add(weight).unwrap();
+        } else {
+            proposal.votes_against = proposal.votes_against.checked_add(weight).unwrap();
+        }
+
+        vote_record.proposal = proposal.key();
+        vote_record.voter = ctx.accounts.voter.key();
+        vote_record.support = support;
+        vote_record.weight = weight;
+        vote_record.bump = ctx.bumps.vote_record;
+
+        emit!(VoteCast {
+            proposal_id: proposal.proposal_id,
+            voter: vote_record.voter,
+            support,
+            weight,
+        });
+
+        Ok(())
+    }
+
+    /// Closes voting on a proposal. A passed proposal is queued behind the admin
+    /// timelock like any other parameter change; pass the next `pending_action`
+    /// account in that case and leave it out otherwise.
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let config = &mut ctx.accounts.protocol_config;
+        let clock = Clock::get()?;
+
+        require!(proposal.status == ProposalStatus::Active, ChainProofError::ProposalNotActive);
+        require!(clock.unix_timestamp >= proposal.voting_ends_at, ChainProofError::VotingNotEnded);
+
+        let total_votes = proposal.votes_for.checked_add(proposal.votes_against).unwrap();
+        let approved = (proposal.votes_for as u128).checked_mul(10000).unwrap()
+            > (total_votes as u128).checked_mul(GOVERNANCE_APPROVAL_BPS as u128).unwrap();
+        let passed = total_votes >= GOVERNANCE_QUORUM && approved;
+
+        let mut action_id = None;
+        if passed {
+            let action = ctx
+                .accounts
+                .pending_action
+                .as_mut()
+                .ok_or(ChainProofError::MissingPendingAction)?;
+
+            action.action_id = config.next_action_id;
+            action.proposer = proposal.proposer;
+            action.change = proposal.change.clone();
+            action.queued_at = clock.unix_timestamp;
+            action.eta = clock.unix_timestamp.checked_add(config.admin_timelock).unwrap();
+            action.expires_at = action.eta.checked_add(ADMIN_ACTION_GRACE_PERIOD).unwrap();
+            action.bump = ctx.bumps.pending_action.unwrap();
+
+            config.next_action_id = config.next_action_id.checked_add(1).unwrap();
+            action_id = Some(action.action_id);
+            proposal.status = ProposalStatus::Queued;
+
+            emit!(AdminActionQueued {
+                action_id: action.action_id,
+                change: action.change.clone(),
+                proposer: action.proposer,
+                eta: action.eta,
+                expires_at: action.expires_at,
+            });
+        } else {
+            require!(
+                ctx.accounts.pending_action.is_none(),
+                ChainProofError::UnexpectedPendingAction
+            );
+            proposal.status = ProposalStatus::Defeated;
+        }
+
+        emit!(ProposalFinalized {
+            proposal_id: proposal.proposal_id,
+            status: proposal.status,
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            action_id,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     // ============================================
     // REWARD POOL
     // ============================================
@@ -93,6 +755,7 @@ pub mod chainproof_protocol {
         pool.distribution_interval = 604800; // 1 week
         pool.developer_share_bps = 6000; // 60%
         pool.user_share_bps = 4000; // 40%
+        pool.pending_authority = None;
         pool.bump = ctx.bumps.reward_pool;
 
         emit!(RewardPoolInitialized {
@@ -168,6 +831,78 @@ pub mod chainproof_protocol {
         Ok(())
     }
 
+    pub fn initialize_insurance_fund(ctx: Context<InitializeInsuranceFund>) -> Result<()> {
+        emit!(InsuranceFundInitialized {
+            token_account: ctx.accounts.insurance_fund.key(),
+            authority: ctx.accounts.super_admin.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Pays out of the insurance fund, e.g. to compensate users of a confirmed rug.
+    pub fn disburse_insurance_fund(ctx: Context<DisburseInsuranceFund>, amount: u64) -> Result<()> {
+        require!(amount > 0, ChainProofError::InvalidRewardAmount);
+        require!(
+            amount <= ctx.accounts.insurance_fund.amount,
+            ChainProofError::InsufficientPoolBalance
+        );
+
+        let seeds = &[b"protocol_config".as_ref(), &[ctx.accounts.protocol_config.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.insurance_fund.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.protocol_config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(InsuranceFundDisbursed {
+            recipient: ctx.accounts.recipient_token_account.key(),
+            treasurer: ctx.accounts.treasurer.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// The pending authority is staged by a queued `RewardPoolAuthority` admin action.
+    pub fn accept_reward_pool_authority(ctx: Context<AcceptRewardPoolAuthority>) -> Result<()> {
+        let pool = &mut ctx.accounts.reward_pool;
+        let previous_authority = pool.authority;
+
+        pool.authority = ctx.accounts.new_authority.key();
+        pool.pending_authority = None;
+
+        emit!(AuthorityHandoverAccepted {
+            target: pool.key(),
+            previous_authority,
+            new_authority: pool.authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_reward_pool_authority(ctx: Context<ManageRewardPoolAuthority>) -> Result<()> {
+        let pool = &mut ctx.accounts.reward_pool;
+
+        let pending_authority = pool
+            .pending_authority
+            .take()
+            .ok_or(ChainProofError::NoPendingAuthority)?;
+
+        emit!(AuthorityHandoverCancelled {
+            target: pool.key(),
+            current_authority: pool.authority,
+            cancelled_authority: pending_authority,
+        });
+
+        Ok(())
+    }
+
     // ============================================
     // USER PROFILES
     // ============================================
@@ -230,6 +965,7 @@ pub mod chainproof_protocol {
 
         registry.authority = ctx.accounts.authority.key();
         registry.total_developers = 0;
+        registry.pending_authority = None;
         registry.bump = ctx.bumps.developer_registry;
 
         emit!(DeveloperRegistryInitialized {
@@ -255,21 +991,62 @@ pub mod chainproof_protocol {
         Ok(())
     }
 
-    // ============================================
+    /// The pending authority is staged by a queued `DeveloperRegistryAuthority` admin action.
+    pub fn accept_developer_registry_authority(
+        ctx: Context<AcceptDeveloperRegistryAuthority>,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.developer_registry;
+        let previous_authority = registry.authority;
+
+        registry.authority = ctx.accounts.new_authority.key();
+        registry.pending_authority = None;
+
+        emit!(AuthorityHandoverAccepted {
+            target: registry.key(),
+            previous_authority,
+            new_authority: registry.authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_developer_registry_authority(
+        ctx: Context<ManageDeveloperRegistryAuthority>,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.developer_registry;
+
+        let pending_authority = registry
+            .pending_authority
+            .take()
+            .ok_or(ChainProofError::NoPendingAuthority)?;
+
+        emit!(AuthorityHandoverCancelled {
+            target: registry.key(),
+            current_authority: registry.authority,
+            cancelled_authority: pending_authority,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
     // STAKING SYSTEM
     // ============================================
 
+    pub fn initialize_receipt_mint(ctx: Context<InitializeReceiptMint>) -> Result<()> {
+        emit!(ReceiptMintInitialized {
+            mint: ctx.accounts.receipt_mint.key(),
+            authority: ctx.accounts.super_admin.key(),
+        });
+
+        Ok(())
+    }
+
     pub fn stake_on_project(
         ctx: Context<StakeOnProject>,
         amount: u64,
+        lockup: LockupTier,
     ) -> Result<()> {
-        let project_stakes = &mut ctx.accounts.project_stakes;
-        let user_stake = &mut ctx.accounts.user_stake;
-        let user_profile = &mut ctx.accounts.user_profile;
-        let clock = Clock::get()?;
-
-        require!(amount > 0, ChainProofError::InvalidStakeAmount);
-
         // Transfer stake tokens from user to stake vault
         let cpi_accounts = Transfer {
             from: ctx.accounts.user_token_account.to_account_info(),
@@ -281,37 +1058,122 @@ pub mod chainproof_protocol {
         token::transfer(cpi_ctx, amount)?;
 
         // Initialize or update user stake
+        let clock = Clock::get()?;
+        let user_stake = &mut ctx.accounts.user_stake;
         user_stake.user = ctx.accounts.user.key();
         user_stake.project_mint = ctx.accounts.project_mint.key();
-        user_stake.amount = user_stake.amount.checked_add(amount).unwrap();
-        user_stake.staked_at = clock.unix_timestamp;
-        user_stake.unstake_requested_at = None;
         user_stake.bump = ctx.bumps.user_stake;
 
-        // Update project stakes
-        project_stakes.total_stakes = project_stakes.total_stakes.checked_add(1).unwrap();
+        record_stake(
+            &mut ctx.accounts.project_stakes,
+            user_stake,
+            &mut ctx.accounts.user_profile,
+            amount,
+            lockup,
+            clock.unix_timestamp,
+        )?;
+        user_stake.receipt_balance = user_stake.receipt_balance.checked_add(amount).unwrap();
+        update_verification_level(
+            &mut ctx.accounts.project_stakes,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
 
-        // Check for verification
-        if project_stakes.total_stakes >= VERIFICATION_THRESHOLD && !project_stakes.is_verified {
-            project_stakes.is_verified = true;
-            emit!(ProjectVerified {
-                project_mint: project_stakes.project_mint,
-                total_stakes: project_stakes.total_stakes,
-            });
-        }
+        // Mint liquid receipts 1:1 for the new stake
+        mint_receipts(
+            &ctx.accounts.receipt_mint.to_account_info(),
+            &ctx.accounts.user_receipt_account.to_account_info(),
+            &ctx.accounts.receipt_mint_authority.to_account_info(),
+            ctx.bumps.receipt_mint_authority,
+            &ctx.accounts.token_program.to_account_info(),
+            amount,
+        )
+    }
 
-        // Update user profile
-        user_profile.total_stakes = user_profile.total_stakes.checked_add(1).unwrap();
-        user_profile.reward_points = user_profile.reward_points.checked_add(amount).unwrap();
+    // Stakes on several projects at once. Each entry takes STAKE_BATCH_ACCOUNTS remaining
+    // accounts in order: project_stakes, user_stake, stake_vault, risk_attestation (the PDA,
+    // even if the project was never attested). Missing user_stake PDAs are created.
+    pub fn stake_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StakeBatch<'info>>,
+        entries: Vec<StakeBatchEntry>,
+    ) -> Result<()> {
+        require!(
+            !entries.is_empty()
+                && entries.len() <= MAX_STAKE_BATCH_ENTRIES
+                && ctx.remaining_accounts.len() == entries.len() * STAKE_BATCH_ACCOUNTS,
+            ChainProofError::InvalidBatch
+        );
 
-        emit!(Staked {
-            user: ctx.accounts.user.key(),
-            project_mint: ctx.accounts.project_mint.key(),
-            amount,
-            total_stakes: project_stakes.total_stakes,
-        });
+        let clock = Clock::get()?;
+        for (entry, accounts) in entries
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(STAKE_BATCH_ACCOUNTS))
+        {
+            let [project_stakes_info, user_stake_info, stake_vault_info, attestation_info] = accounts else {
+                return err!(ChainProofError::InvalidBatch);
+            };
+
+            let mut project_stakes = load_project_stakes(project_stakes_info, &entry.project_mint)?;
+            let attestation = load_risk_attestation(attestation_info, &entry.project_mint)?;
+
+            let stake_vault = Account::<TokenAccount>::try_from(stake_vault_info)?;
+            require!(
+                stake_vault.mint == STAKE_TOKEN_MINT && stake_vault.owner == project_stakes_info.key(),
+                ChainProofError::InvalidTokenAccount
+            );
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: stake_vault_info.clone(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            token::transfer(cpi_ctx, entry.amount)?;
+
+            let mut user_stake = load_or_init_user_stake(
+                user_stake_info,
+                &ctx.accounts.user.to_account_info(),
+                &entry.project_mint,
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+
+            record_stake(
+                &mut project_stakes,
+                &mut user_stake,
+                &mut ctx.accounts.user_profile,
+                entry.amount,
+                entry.lockup,
+                clock.unix_timestamp,
+            )?;
+            user_stake.receipt_balance = user_stake.receipt_balance.checked_add(entry.amount).unwrap();
+            update_verification_level(
+                &mut project_stakes,
+                &ctx.accounts.protocol_config,
+                attestation.as_ref(),
+                clock.unix_timestamp,
+            );
+
+            // Write back before the next entry so repeated projects see the updated state
+            project_stakes.try_serialize(&mut &mut project_stakes_info.try_borrow_mut_data()?[..])?;
+            user_stake.try_serialize(&mut &mut user_stake_info.try_borrow_mut_data()?[..])?;
+        }
 
-        Ok(())
+        // One receipt mint for the whole batch
+        let total_amount = entries
+            .iter()
+            .try_fold(0u64, |total, entry| total.checked_add(entry.amount))
+            .ok_or(ChainProofError::InvalidStakeAmount)?;
+
+        mint_receipts(
+            &ctx.accounts.receipt_mint.to_account_info(),
+            &ctx.accounts.user_receipt_account.to_account_info(),
+            &ctx.accounts.receipt_mint_authority.to_account_info(),
+            ctx.bumps.receipt_mint_authority,
+            &ctx.accounts.token_program.to_account_info(),
+            total_amount,
+        )
     }
 
     pub fn initialize_project_stakes(ctx: Context<InitializeProjectStakes>) -> Result<()> {
@@ -319,462 +1181,5642 @@ pub mod chainproof_protocol {
 
         project_stakes.project_mint = ctx.accounts.project_mint.key();
         project_stakes.total_stakes = 0;
-        project_stakes.is_verified = false;
+        project_stakes.unique_stakers = 0;
+        project_stakes.total_staked = 0;
+        project_stakes.total_pending_unstake = 0;
+        project_stakes.reward_per_share = 0;
+        project_stakes.undistributed_rewards = 0;
+        project_stakes.reward_streams = Vec::new();
+        project_stakes.status = ProjectStatus::Active;
+        project_stakes.flagged_at = 0;
+        project_stakes.flag_cleared_at = 0;
+        project_stakes.slash_rate_bps = 0;
+        project_stakes.total_reward_weight = 0;
+        project_stakes.total_challenged = 0;
+        project_stakes.unique_challengers = 0;
+        project_stakes.challenger_rewards = 0;
+        project_stakes.verification_level = VerificationLevel::None;
         project_stakes.bump = ctx.bumps.project_stakes;
 
         Ok(())
     }
 
-    pub fn request_unstake(ctx: Context<RequestUnstake>) -> Result<()> {
+    // Unstaking is deliberately not gated by the staking pause so funds can always exit
+    pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
         let user_stake = &mut ctx.accounts.user_stake;
+        let project_stakes = &mut ctx.accounts.project_stakes;
         let clock = Clock::get()?;
 
-        require!(user_stake.amount > 0, ChainProofError::NoStakeFound);
-        require!(
-            user_stake.unstake_requested_at.is_none(),
-            ChainProofError::UnstakeAlreadyRequested
+        open_unstake_request(
+            user_stake,
+            project_stakes,
+            amount,
+            ctx.accounts.protocol_config.unstake_cooldown,
+            clock.unix_timestamp,
+        )?;
+        update_verification_level(
+            project_stakes,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
         );
 
-        user_stake.unstake_requested_at = Some(clock.unix_timestamp);
-
         emit!(UnstakeRequested {
             user: user_stake.user,
             project_mint: user_stake.project_mint,
-            cooldown_ends: clock.unix_timestamp + UNSTAKE_COOLDOWN,
+            amount,
+            cooldown_ends: user_stake.unstake_available_at,
         });
 
         Ok(())
     }
 
-    pub fn complete_unstake(ctx: Context<CompleteUnstake>) -> Result<()> {
+    pub fn cancel_unstake(ctx: Context<CancelUnstake>) -> Result<()> {
         let user_stake = &mut ctx.accounts.user_stake;
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        let clock = Clock::get()?;
+
+        let amount = cancel_unstake_request(user_stake, project_stakes, clock.unix_timestamp)?;
+        update_verification_level(
+            project_stakes,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
+
+        emit!(UnstakeCancelled {
+            user: user_stake.user,
+            project_mint: user_stake.project_mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn complete_unstake(ctx: Context<CompleteUnstake>) -> Result<()> {
+        let user_stake = &ctx.accounts.user_stake;
         let clock = Clock::get()?;
 
-        // Check cooldown period
-        let requested_at = user_stake.unstake_requested_at.ok_or(ChainProofError::UnstakeNotRequested)?;
+        // The cooldown is fixed when the unstake is requested, so later config
+        // changes don't move it
+        require!(
+            user_stake.unstake_requested_at.is_some(),
+            ChainProofError::UnstakeNotRequested
+        );
         require!(
-            clock.unix_timestamp >= requested_at + UNSTAKE_COOLDOWN,
+            clock.unix_timestamp >= user_stake.unstake_available_at,
             ChainProofError::CooldownNotComplete
         );
 
-        let amount = user_stake.amount;
+        ctx.accounts.withdraw_pending(0, None)?;
 
-        // Store bump before borrowing project_stakes mutably
-        let project_stakes_bump = ctx.accounts.project_stakes.bump;
-        let project_mint_key = ctx.accounts.project_mint.key();
+        Ok(())
+    }
+
+    // Same as complete_unstake but skips the cooldown in exchange for a penalty paid to the reward pool
+    pub fn emergency_unstake(ctx: Context<EmergencyUnstake>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let unstake = &mut ctx.accounts.unstake;
+        open_emergency_request(
+            &mut unstake.user_stake,
+            &mut unstake.project_stakes,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        let penalty_bps = ctx.accounts.unstake.protocol_config.emergency_unstake_penalty_bps;
+        let pool_token_account = ctx.accounts.pool_token_account.to_account_info();
+        let penalty = ctx
+            .accounts
+            .unstake
+            .withdraw_pending(penalty_bps, Some(pool_token_account))?;
 
-        // Transfer stake tokens back to user
+        let reward_pool = &mut ctx.accounts.reward_pool;
+        reward_pool.total_deposited = reward_pool.total_deposited.checked_add(penalty).unwrap();
+
+        Ok(())
+    }
+
+    // Moves active stake between projects without going through the unstake cooldown. The
+    // moved amount is staked on the destination under `lockup`, like any new stake.
+    pub fn restake_to_project(
+        ctx: Context<RestakeToProject>,
+        amount: u64,
+        lockup: LockupTier,
+    ) -> Result<()> {
+        require!(amount > 0, ChainProofError::InvalidStakeAmount);
+        require!(
+            ctx.accounts.from_project_stakes.status == ProjectStatus::Active
+                && ctx.accounts.to_project_stakes.status == ProjectStatus::Active,
+            ChainProofError::ProjectNotActive
+        );
+
+        let clock = Clock::get()?;
+        let from_project = &mut ctx.accounts.from_project_stakes;
+        let MovedStake {
+            receipts: moved_receipts,
+            remaining,
+        } = move_out_of_position(
+            &mut ctx.accounts.from_user_stake,
+            from_project,
+            &mut ctx.accounts.user_profile,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        update_verification_level(
+            from_project,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.from_risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
+
+        // Move the tokens between the two project vaults
+        let from_bump = from_project.bump;
+        let from_mint_key = ctx.accounts.from_project_mint.key();
         let seeds = &[
-            b"stake_vault",
-            project_mint_key.as_ref(),
-            &[project_stakes_bump],
+            b"project_stakes",
+            from_mint_key.as_ref(),
+            &[from_bump],
         ];
         let signer = &[&seeds[..]];
 
         let cpi_accounts = Transfer {
-            from: ctx.accounts.stake_vault.to_account_info(),
-            to: ctx.accounts.user_token_account.to_account_info(),
-            authority: ctx.accounts.project_stakes.to_account_info(),
+            from: ctx.accounts.from_stake_vault.to_account_info(),
+            to: ctx.accounts.to_stake_vault.to_account_info(),
+            authority: ctx.accounts.from_project_stakes.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
         token::transfer(cpi_ctx, amount)?;
 
-        // Now update counts (after CPI is complete)
-        let project_stakes = &mut ctx.accounts.project_stakes;
-        let user_profile = &mut ctx.accounts.user_profile;
+        // Stake it on the destination. A lockup still running there covers the whole
+        // position, so the moved amount has to commit to it afresh to share its multiplier
+        let to_stake = &mut ctx.accounts.to_user_stake;
+        require!(
+            clock.unix_timestamp >= to_stake.unlock_at || lockup >= to_stake.lockup,
+            ChainProofError::LockupRequired
+        );
+        to_stake.user = ctx.accounts.user.key();
+        to_stake.project_mint = ctx.accounts.to_project_mint.key();
+        to_stake.bump = ctx.bumps.to_user_stake;
+        to_stake.receipt_balance = to_stake.receipt_balance.checked_add(moved_receipts).unwrap();
+
+        record_stake(
+            &mut ctx.accounts.to_project_stakes,
+            to_stake,
+            &mut ctx.accounts.user_profile,
+            amount,
+            lockup,
+            clock.unix_timestamp,
+        )?;
+        update_verification_level(
+            &mut ctx.accounts.to_project_stakes,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.to_risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
 
-        project_stakes.total_stakes = project_stakes.total_stakes.saturating_sub(1);
-        user_profile.total_stakes = user_profile.total_stakes.saturating_sub(1);
-        user_profile.reward_points = user_profile.reward_points.saturating_sub(amount);
+        emit!(Restaked {
+            user: ctx.accounts.user.key(),
+            from_project_mint: from_mint_key,
+            to_project_mint: ctx.accounts.to_project_mint.key(),
+            amount,
+            remaining,
+        });
 
-        // Check if project loses verification
-        if project_stakes.total_stakes < VERIFICATION_THRESHOLD && project_stakes.is_verified {
-            project_stakes.is_verified = false;
+        if remaining == 0 && !ctx.accounts.from_user_stake.has_unclaimed_rewards() {
+            emit!(UserStakeClosed {
+                user: ctx.accounts.user.key(),
+                project_mint: from_mint_key,
+            });
+            ctx.accounts.from_user_stake.close(ctx.accounts.user.to_account_info())?;
         }
 
-        // Reset user stake
-        user_stake.amount = 0;
-        user_stake.unstake_requested_at = None;
+        Ok(())
+    }
 
-        emit!(Unstaked {
-            user: user_stake.user,
-            project_mint: user_stake.project_mint,
+    // Receipts are fungible, so whoever holds them can redeem them against any other
+    // staker's position on the project, at that position's own receipt rate. The redeemed
+    // stake stays in the vault and moves into the holder's position under `lockup`, from
+    // where it unstakes through the normal cooldown. The owner can close an emptied position.
+    pub fn redeem_receipts(
+        ctx: Context<RedeemReceipts>,
+        receipts: u64,
+        lockup: LockupTier,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.project_stakes.status == ProjectStatus::Active,
+            ChainProofError::ProjectNotActive
+        );
+
+        let clock = Clock::get()?;
+        let amount = redeem_from_position(
+            &mut ctx.accounts.owner_stake,
+            &mut ctx.accounts.project_stakes,
+            &mut ctx.accounts.owner_profile,
+            receipts,
+            clock.unix_timestamp,
+        )?;
+
+        burn_receipts(
+            &ctx.accounts.receipt_mint.to_account_info(),
+            &ctx.accounts.holder_receipt_account.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            receipts,
+        )?;
+
+        // Same rule as restaking onto a locked position
+        let holder_stake = &mut ctx.accounts.holder_stake;
+        require!(
+            clock.unix_timestamp >= holder_stake.unlock_at || lockup >= holder_stake.lockup,
+            ChainProofError::LockupRequired
+        );
+        holder_stake.user = ctx.accounts.holder.key();
+        holder_stake.project_mint = ctx.accounts.project_mint.key();
+        holder_stake.bump = ctx.bumps.holder_stake;
+
+        record_stake(
+            &mut ctx.accounts.project_stakes,
+            holder_stake,
+            &mut ctx.accounts.holder_profile,
+            amount,
+            lockup,
+            clock.unix_timestamp,
+        )?;
+        update_verification_level(
+            &mut ctx.accounts.project_stakes,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
+
+        emit!(ReceiptsRedeemed {
+            holder: ctx.accounts.holder.key(),
+            owner: ctx.accounts.owner.key(),
+            project_mint: ctx.accounts.project_mint.key(),
+            receipts,
             amount,
         });
 
         Ok(())
     }
-}
 
-// ============================================
-// ACCOUNT STRUCTS
-// ============================================
+    pub fn close_user_stake(ctx: Context<CloseUserStake>) -> Result<()> {
+        let user_stake = &ctx.accounts.user_stake;
 
-#[account]
-pub struct TokenEntry {
-    pub authority: Pubkey,  // 32
-    pub mint: Pubkey,       // 32
-    pub name: String,       // 4 + 50
-    pub symbol: String,     // 4 + 10
-    pub ipfs_hash: String,  // 4 + 100
-    pub timestamp: i64,     // 8
-    pub bump: u8,           // 1
-}
+        emit!(UserStakeClosed {
+            user: user_stake.user,
+            project_mint: user_stake.project_mint,
+        });
 
-impl TokenEntry {
-    pub const LEN: usize = 8 + 32 + 32 + (4 + 50) + (4 + 10) + (4 + 100) + 8 + 1;
-}
+        Ok(())
+    }
 
-#[account]
-pub struct RewardPool {
-    pub authority: Pubkey,          // 32
-    pub total_deposited: u64,       // 8
-    pub total_distributed: u64,     // 8
-    pub last_distribution: i64,     // 8
-    pub distribution_interval: i64, // 8
-    pub developer_share_bps: u16,   // 2 (basis points: 6000 = 60%)
-    pub user_share_bps: u16,        // 2
-    pub bump: u8,                   // 1
-}
+    /// Permissionless: ends a lockup that has run out so the position stops earning
+    /// the boosted rate without waiting for its owner to touch it.
+    pub fn expire_lockup(ctx: Context<ExpireLockup>) -> Result<()> {
+        let user_stake = &mut ctx.accounts.user_stake;
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        let clock = Clock::get()?;
 
-impl RewardPool {
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 2 + 2 + 1;
-}
+        project_stakes.accrue_reward_streams(clock.unix_timestamp);
+        user_stake.settle_rewards(project_stakes);
+        let previous_lockup = user_stake.lockup;
+        require!(
+            release_expired_lockup(user_stake, project_stakes, clock.unix_timestamp),
+            ChainProofError::LockupNotExpired
+        );
 
-#[account]
-pub struct UserProfile {
-    pub wallet: Pubkey,             // 32
-    pub username: String,           // 4 + 32
-    pub referral_code: Option<String>, // 1 + 4 + 32
-    pub is_developer: bool,         // 1
-    pub total_stakes: u64,          // 8
-    pub reward_points: u64,         // 8
-    pub created_at: i64,            // 8
-    pub bump: u8,                   // 1
-}
+        emit!(LockupExpired {
+            user: user_stake.user,
+            project_mint: user_stake.project_mint,
+            lockup: previous_lockup,
+            unlock_at: user_stake.unlock_at,
+        });
 
-impl UserProfile {
-    pub const LEN: usize = 8 + 32 + (4 + 32) + (1 + 4 + 32) + 1 + 8 + 8 + 8 + 1;
-}
+        Ok(())
+    }
 
-#[account]
-pub struct DeveloperRegistry {
-    pub authority: Pubkey,          // 32
-    pub total_developers: u64,      // 8
-    pub bump: u8,                   // 1
-}
+    // ============================================
+    // STAKING REWARDS
+    // ============================================
 
-impl DeveloperRegistry {
-    pub const LEN: usize = 8 + 32 + 8 + 1;
-}
+    pub fn fund_project_rewards(ctx: Context<FundProjectRewards>, amount: u64) -> Result<()> {
+        require!(amount > 0, ChainProofError::InvalidRewardAmount);
+        require!(
+            ctx.accounts.project_stakes.status == ProjectStatus::Active,
+            ChainProofError::ProjectNotActive
+        );
 
-#[account]
-pub struct ProjectStakes {
-    pub project_mint: Pubkey,       // 32
-    pub total_stakes: u64,          // 8
-    pub is_verified: bool,          // 1
-    pub bump: u8,                   // 1
-}
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.funder_token_account.to_account_info(),
+            to: ctx.accounts.reward_vault.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
 
-impl ProjectStakes {
-    pub const LEN: usize = 8 + 32 + 8 + 1 + 1;
-}
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        project_stakes.distribute_rewards(amount);
 
-#[account]
-pub struct UserStake {
-    pub user: Pubkey,               // 32
-    pub project_mint: Pubkey,       // 32
-    pub amount: u64,                // 8
-    pub staked_at: i64,             // 8
-    pub unstake_requested_at: Option<i64>, // 1 + 8
-    pub bump: u8,                   // 1
-}
+        emit!(ProjectRewardsFunded {
+            project_mint: project_stakes.project_mint,
+            funder: ctx.accounts.funder.key(),
+            amount,
+            reward_per_share: project_stakes.reward_per_share,
+        });
 
-impl UserStake {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + (1 + 8) + 1;
-}
+        Ok(())
+    }
 
-// ============================================
-// CONTEXTS
-// ============================================
+    pub fn claim_stake_rewards(ctx: Context<ClaimStakeRewards>) -> Result<()> {
+        let clock = Clock::get()?;
+        let amount = take_stake_rewards(
+            &mut ctx.accounts.user_stake,
+            &mut ctx.accounts.project_stakes,
+            clock.unix_timestamp,
+        )?;
 
-#[derive(Accounts)]
-#[instruction(name: String, symbol: String, ipfs_hash: String)]
-pub struct RegisterToken<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        let project_stakes_bump = ctx.accounts.project_stakes.bump;
+        let project_mint_key = ctx.accounts.project_stakes.project_mint;
+        let seeds = &[
+            b"project_stakes",
+            project_mint_key.as_ref(),
+            &[project_stakes_bump],
+        ];
+        let signer = &[&seeds[..]];
 
-    /// CHECK: Token mint being registered
-    pub mint: AccountInfo<'info>,
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.reward_vault.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.project_stakes.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
 
-    #[account(
-        init,
-        payer = authority,
-        space = TokenEntry::LEN,
+        emit!(StakeRewardsClaimed {
+            user: ctx.accounts.user.key(),
+            project_mint: project_mint_key,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // PROJECT-SPONSORED REWARDS
+    // ============================================
+
+    pub fn create_project_reward_vault(ctx: Context<CreateProjectRewardVault>) -> Result<()> {
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        require!(
+            project_stakes.reward_streams.len() < MAX_REWARD_STREAMS,
+            ChainProofError::TooManyRewardStreams
+        );
+
+        let stream_index = project_stakes.reward_streams.len() as u8;
+        project_stakes.reward_streams.push(RewardStream {
+            reward_mint: ctx.accounts.reward_mint.key(),
+            rate: 0,
+            start_time: 0,
+            end_time: 0,
+            last_update: 0,
+            reward_per_share: 0,
+            unallocated: 0,
+        });
+
+        let reward_vault = &mut ctx.accounts.reward_vault;
+        reward_vault.project_mint = ctx.accounts.project_mint.key();
+        reward_vault.reward_mint = ctx.accounts.reward_mint.key();
+        reward_vault.stream_index = stream_index;
+        reward_vault.total_funded = 0;
+        reward_vault.bump = ctx.bumps.reward_vault;
+
+        emit!(ProjectRewardVaultCreated {
+            project_mint: reward_vault.project_mint,
+            reward_mint: reward_vault.reward_mint,
+            stream_index,
+        });
+
+        Ok(())
+    }
+
+    pub fn fund_project_reward_vault(
+        ctx: Context<FundProjectRewardVault>,
+        amount: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            start_time >= clock.unix_timestamp && end_time > start_time,
+            ChainProofError::InvalidRewardSchedule
+        );
+        require!(
+            ctx.accounts.project_stakes.status == ProjectStatus::Active,
+            ChainProofError::ProjectNotActive
+        );
+
+        // Only whole per-second emissions are pulled in; the remainder stays with the funder
+        let duration = (end_time - start_time) as u64;
+        let rate = amount / duration;
+        require!(rate > 0, ChainProofError::InvalidRewardAmount);
+        let emitted = rate.checked_mul(duration).unwrap();
+
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        project_stakes.accrue_reward_streams(clock.unix_timestamp);
+
+        let stream = &mut project_stakes.reward_streams[ctx.accounts.reward_vault.stream_index as usize];
+        require!(
+            clock.unix_timestamp >= stream.end_time,
+            ChainProofError::RewardScheduleActive
+        );
+        stream.rate = rate;
+        stream.start_time = start_time;
+        stream.end_time = end_time;
+        stream.last_update = start_time;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.funder_token_account.to_account_info(),
+            to: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, emitted)?;
+
+        let reward_vault = &mut ctx.accounts.reward_vault;
+        reward_vault.total_funded = reward_vault.total_funded.checked_add(emitted).unwrap();
+
+        emit!(SponsoredRewardsFunded {
+            project_mint: reward_vault.project_mint,
+            reward_mint: reward_vault.reward_mint,
+            amount: emitted,
+            rate,
+            start_time,
+            end_time,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_sponsored_rewards(ctx: Context<ClaimSponsoredRewards>) -> Result<()> {
+        let clock = Clock::get()?;
+        ctx.accounts.project_stakes.accrue_reward_streams(clock.unix_timestamp);
+
+        let stream_index = ctx.accounts.reward_vault.stream_index as usize;
+        let user_stake = &mut ctx.accounts.user_stake;
+        user_stake.settle_rewards(&ctx.accounts.project_stakes);
+        user_stake.apply_slash(&ctx.accounts.project_stakes);
+        user_stake.checkpoint_rewards(&ctx.accounts.project_stakes);
+        release_expired_lockup(user_stake, &mut ctx.accounts.project_stakes, clock.unix_timestamp);
+
+        let amount = user_stake.sponsored_rewards[stream_index].pending_rewards;
+        require!(amount > 0, ChainProofError::NoRewardsToClaim);
+        user_stake.sponsored_rewards[stream_index].pending_rewards = 0;
+
+        let project_mint_key = ctx.accounts.reward_vault.project_mint;
+        let reward_mint_key = ctx.accounts.reward_vault.reward_mint;
+        let seeds = &[
+            b"project_reward_vault",
+            project_mint_key.as_ref(),
+            reward_mint_key.as_ref(),
+            &[ctx.accounts.reward_vault.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.reward_vault.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(SponsoredRewardsClaimed {
+            user: ctx.accounts.user.key(),
+            project_mint: project_mint_key,
+            reward_mint: reward_mint_key,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Returns sponsored emissions that were released while nothing was staked (or the
+    /// project was flagged or slashed) to the project authority.
+    pub fn reclaim_unallocated_rewards(ctx: Context<ReclaimUnallocatedRewards>) -> Result<()> {
+        let clock = Clock::get()?;
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        project_stakes.accrue_reward_streams(clock.unix_timestamp);
+
+        let stream = &mut project_stakes.reward_streams[ctx.accounts.reward_vault.stream_index as usize];
+        let amount = stream.unallocated;
+        require!(amount > 0, ChainProofError::NoRewardsToClaim);
+        stream.unallocated = 0;
+
+        let project_mint_key = ctx.accounts.reward_vault.project_mint;
+        let reward_mint_key = ctx.accounts.reward_vault.reward_mint;
+        let seeds = &[
+            b"project_reward_vault",
+            project_mint_key.as_ref(),
+            reward_mint_key.as_ref(),
+            &[ctx.accounts.reward_vault.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.authority_token_account.to_account_info(),
+            authority: ctx.accounts.reward_vault.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(UnallocatedRewardsReclaimed {
+            project_mint: project_mint_key,
+            reward_mint: reward_mint_key,
+            authority: ctx.accounts.authority.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // SLASHING
+    // ============================================
+
+    pub fn flag_project(ctx: Context<ModerateProject>) -> Result<()> {
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        let clock = Clock::get()?;
+
+        require!(
+            project_stakes.status == ProjectStatus::Active,
+            ChainProofError::ProjectNotActive
+        );
+
+        // Credit emissions up to now; nothing accrues while the project is under review
+        project_stakes.accrue_reward_streams(clock.unix_timestamp);
+        project_stakes.status = ProjectStatus::Flagged;
+        project_stakes.flagged_at = clock.unix_timestamp;
+
+        update_verification_level(
+            project_stakes,
+            &ctx.accounts.protocol_config,
+            None,
+            clock.unix_timestamp,
+        );
+
+        emit!(ProjectFlagged {
+            project_mint: project_stakes.project_mint,
+            moderator: ctx.accounts.moderator.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn clear_project_flag(ctx: Context<ClearProjectFlag>) -> Result<()> {
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        let clock = Clock::get()?;
+
+        require!(
+            project_stakes.status == ProjectStatus::Flagged,
+            ChainProofError::ProjectNotFlagged
+        );
+
+        // Skip the flagged period so streams resume from now
+        project_stakes.accrue_reward_streams(clock.unix_timestamp);
+        project_stakes.status = ProjectStatus::Active;
+        project_stakes.flag_cleared_at = clock.unix_timestamp;
+
+        update_verification_level(
+            project_stakes,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
+
+        emit!(ProjectFlagCleared {
+            project_mint: project_stakes.project_mint,
+            moderator: ctx.accounts.moderator.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn slash_project(ctx: Context<SlashProject>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(
+            ctx.accounts.project_stakes.status == ProjectStatus::Flagged,
+            ChainProofError::ProjectNotFlagged
+        );
+
+        // Slash what positions hold rather than the vault balance, which anyone can add to
+        let rate_bps = ctx.accounts.protocol_config.slash_rate_bps;
+        let project_stakes = &ctx.accounts.project_stakes;
+        let staked_balance = project_stakes
+            .total_staked
+            .checked_add(project_stakes.total_pending_unstake)
+            .unwrap();
+        let to_challengers = project_stakes.total_challenged > 0;
+        let slashed_amount = slash_project_stakes(&mut ctx.accounts.project_stakes, rate_bps, to_challengers);
+
+        // Challengers share the slashed stake; without any it goes where the config says
+        let slash_destination = ctx.accounts.protocol_config.slash_destination;
+        let destination = if to_challengers {
+            ctx.accounts
+                .challenge_vault
+                .as_ref()
+                .ok_or(ChainProofError::MissingChallengeVault)?
+                .to_account_info()
+        } else if slash_destination == SlashDestination::InsuranceFund {
+            ctx.accounts
+                .insurance_fund
+                .as_ref()
+                .ok_or(ChainProofError::MissingInsuranceFund)?
+                .to_account_info()
+        } else {
+            ctx.accounts.pool_token_account.to_account_info()
+        };
+        let destination_key = destination.key();
+
+        if slashed_amount > 0 {
+            let project_stakes_bump = ctx.accounts.project_stakes.bump;
+            let project_mint_key = ctx.accounts.project_mint.key();
+            let seeds = &[
+                b"project_stakes",
+                project_mint_key.as_ref(),
+                &[project_stakes_bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.stake_vault.to_account_info(),
+                to: destination,
+                authority: ctx.accounts.project_stakes.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, slashed_amount)?;
+
+            if !to_challengers && slash_destination == SlashDestination::RewardPool {
+                let reward_pool = &mut ctx.accounts.reward_pool;
+                reward_pool.total_deposited = reward_pool.total_deposited.checked_add(slashed_amount).unwrap();
+            }
+        }
+
+        let project_stakes = &ctx.accounts.project_stakes;
+        let slash_event = &mut ctx.accounts.slash_event;
+        slash_event.project_mint = project_stakes.project_mint;
+        slash_event.slashed_by = ctx.accounts.moderator.key();
+        slash_event.rate_bps = rate_bps;
+        slash_event.staked_balance = staked_balance;
+        slash_event.slashed_amount = slashed_amount;
+        slash_event.slashed_at = clock.unix_timestamp;
+        slash_event.bump = ctx.bumps.slash_event;
+
+        emit!(ProjectSlashed {
+            project_mint: project_stakes.project_mint,
+            moderator: ctx.accounts.moderator.key(),
+            rate_bps,
+            slashed_amount,
+            challenger_rewards: project_stakes.challenger_rewards,
+            destination: destination_key,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // CHALLENGE STAKES
+    // ============================================
+
+    pub fn stake_against_project(ctx: Context<StakeAgainstProject>, amount: u64) -> Result<()> {
+        require!(amount > 0, ChainProofError::InvalidStakeAmount);
+        require!(
+            ctx.accounts.project_stakes.status == ProjectStatus::Active,
+            ChainProofError::ProjectNotActive
+        );
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.challenger_token_account.to_account_info(),
+            to: ctx.accounts.challenge_vault.to_account_info(),
+            authority: ctx.accounts.challenger.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let clock = Clock::get()?;
+        let challenge_stake = &mut ctx.accounts.challenge_stake;
+        let project_stakes = &mut ctx.accounts.project_stakes;
+
+        if challenge_stake.amount == 0 {
+            project_stakes.unique_challengers = project_stakes.unique_challengers.checked_add(1).unwrap();
+        }
+        project_stakes.total_challenged = project_stakes.total_challenged.checked_add(amount).unwrap();
+
+        // Topping up restarts the dispute window for the whole position
+        challenge_stake.challenger = ctx.accounts.challenger.key();
+        challenge_stake.project_mint = ctx.accounts.project_mint.key();
+        challenge_stake.amount = challenge_stake.amount.checked_add(amount).unwrap();
+        challenge_stake.staked_at = clock.unix_timestamp;
+        challenge_stake.bump = ctx.bumps.challenge_stake;
+
+        update_verification_level(
+            project_stakes,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
+
+        emit!(ChallengeStaked {
+            challenger: challenge_stake.challenger,
+            project_mint: challenge_stake.project_mint,
+            amount,
+            total_challenged: project_stakes.total_challenged,
+        });
+
+        Ok(())
+    }
+
+    // Not gated by the staking pause so challengers can always exit
+    pub fn withdraw_challenge(ctx: Context<WithdrawChallenge>) -> Result<()> {
+        let clock = Clock::get()?;
+        let amount = ctx.accounts.challenge_stake.amount;
+
+        let ChallengePayout {
+            payout,
+            penalty,
+            forfeited,
+        } = challenge_payout(
+            &ctx.accounts.challenge_stake,
+            &ctx.accounts.project_stakes,
+            &ctx.accounts.protocol_config,
+            clock.unix_timestamp,
+        )?;
+
+        let project_stakes_bump = ctx.accounts.project_stakes.bump;
+        let project_mint_key = ctx.accounts.project_mint.key();
+        let seeds = &[
+            b"project_stakes",
+            project_mint_key.as_ref(),
+            &[project_stakes_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.challenge_vault.to_account_info(),
+            to: ctx.accounts.challenger_token_account.to_account_info(),
+            authority: ctx.accounts.project_stakes.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, payout)?;
+
+        if penalty > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.challenge_vault.to_account_info(),
+                to: ctx.accounts.reward_vault.to_account_info(),
+                authority: ctx.accounts.project_stakes.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, penalty)?;
+        }
+
+        // A forfeited share goes to the reward pool, as a slash without challengers would
+        if forfeited > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.challenge_vault.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.project_stakes.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, forfeited)?;
+
+            let reward_pool = &mut ctx.accounts.reward_pool;
+            reward_pool.total_deposited = reward_pool.total_deposited.checked_add(forfeited).unwrap();
+        }
+
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        if penalty > 0 {
+            project_stakes.distribute_rewards(penalty);
+        }
+
+        // After a slash the totals stay fixed so every challenger's share is computed the same way
+        if project_stakes.status == ProjectStatus::Active {
+            project_stakes.total_challenged = project_stakes.total_challenged.saturating_sub(amount);
+            project_stakes.unique_challengers = project_stakes.unique_challengers.saturating_sub(1);
+
+            update_verification_level(
+                project_stakes,
+                &ctx.accounts.protocol_config,
+                read_risk_attestation(&ctx.accounts.risk_attestation)?.as_ref(),
+                clock.unix_timestamp,
+            );
+        }
+
+        emit!(ChallengeWithdrawn {
+            challenger: ctx.accounts.challenger.key(),
+            project_mint: project_mint_key,
+            amount,
+            payout,
+            penalty,
+            forfeited,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // RISK ATTESTATIONS
+    // ============================================
+
+    pub fn submit_risk_attestation(
+        ctx: Context<SubmitRiskAttestation>,
+        risk_score: u16,
+        valid_for: i64,
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.risk_attestation;
+        let clock = Clock::get()?;
+
+        require!(risk_score <= MAX_RISK_SCORE, ChainProofError::InvalidRiskScore);
+        require!(
+            valid_for > 0 && valid_for <= MAX_ATTESTATION_TTL,
+            ChainProofError::InvalidAttestationTtl
+        );
+
+        attestation.mint = ctx.accounts.project_mint.key();
+        attestation.attester = ctx.accounts.attester.key();
+        attestation.risk_score = risk_score;
+        attestation.attested_at = clock.unix_timestamp;
+        attestation.expires_at = clock.unix_timestamp.checked_add(valid_for).unwrap();
+        attestation.bump = ctx.bumps.risk_attestation;
+
+        emit!(RiskAttested {
+            mint: attestation.mint,
+            attester: attestation.attester,
+            risk_score,
+            expires_at: attestation.expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: re-evaluates a project's level so expired or worsened
+    /// attestations revoke verification without waiting for a stake change.
+    pub fn refresh_verification(ctx: Context<RefreshVerification>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        update_verification_level(
+            &mut ctx.accounts.project_stakes,
+            &ctx.accounts.protocol_config,
+            read_risk_attestation(&ctx.accounts.risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
+
+        Ok(())
+    }
+
+    // ============================================
+    // SCAM REPORTS
+    // ============================================
+
+    pub fn file_scam_report(ctx: Context<FileScamReport>, evidence_hash: String) -> Result<()> {
+        let report = &mut ctx.accounts.scam_report;
+        let clock = Clock::get()?;
+
+        require!(evidence_hash.len() <= 100, ChainProofError::EvidenceHashTooLong);
+        require!(!evidence_hash.is_empty(), ChainProofError::EvidenceHashRequired);
+
+        // Lock the bond in the report vault
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.reporter_token_account.to_account_info(),
+            to: ctx.accounts.report_vault.to_account_info(),
+            authority: ctx.accounts.reporter.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, SCAM_REPORT_BOND)?;
+
+        report.reporter = ctx.accounts.reporter.key();
+        report.mint = ctx.accounts.token_entry.mint;
+        report.evidence_hash = evidence_hash;
+        report.bond_amount = SCAM_REPORT_BOND;
+        report.status = ReportStatus::Pending;
+        report.created_at = clock.unix_timestamp;
+        report.resolved_at = None;
+        report.resolver = None;
+        report.bump = ctx.bumps.scam_report;
+        report.vault_bump = ctx.bumps.report_vault;
+
+        emit!(ScamReportFiled {
+            reporter: report.reporter,
+            mint: report.mint,
+            evidence_hash: report.evidence_hash.clone(),
+            bond_amount: report.bond_amount,
+            timestamp: report.created_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn resolve_scam_report(ctx: Context<ResolveScamReport>, upheld: bool) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require!(
+            ctx.accounts.scam_report.status == ReportStatus::Pending,
+            ChainProofError::ReportAlreadyResolved
+        );
+
+        let bond_amount = ctx.accounts.scam_report.bond_amount;
+        let report_bump = ctx.accounts.scam_report.bump;
+        let report_mint = ctx.accounts.scam_report.mint;
+        let reporter_key = ctx.accounts.scam_report.reporter;
+        let pool_bump = ctx.accounts.reward_pool.bump;
+
+        let report_seeds = &[
+            b"scam_report",
+            report_mint.as_ref(),
+            reporter_key.as_ref(),
+            &[report_bump],
+        ];
+        let report_signer = &[&report_seeds[..]];
+
+        // Upheld bonds go back to the reporter, rejected bonds go to the reward pool
+        let bond_destination = if upheld {
+            ctx.accounts.reporter_token_account.to_account_info()
+        } else {
+            ctx.accounts.pool_token_account.to_account_info()
+        };
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.report_vault.to_account_info(),
+            to: bond_destination,
+            authority: ctx.accounts.scam_report.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, report_signer);
+        token::transfer(cpi_ctx, bond_amount)?;
+
+        // Return the vault rent to the reporter
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.report_vault.to_account_info(),
+            destination: ctx.accounts.reporter.to_account_info(),
+            authority: ctx.accounts.scam_report.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, report_signer);
+        token::close_account(cpi_ctx)?;
+
+        let mut bounty = 0;
+        if upheld {
+            // Bounty is capped by what the pool currently holds
+            bounty = SCAM_REPORT_BOUNTY.min(ctx.accounts.pool_token_account.amount);
+
+            if bounty > 0 {
+                let pool_seeds = &[b"reward_pool".as_ref(), &[pool_bump]];
+                let pool_signer = &[&pool_seeds[..]];
+
+                let cpi_accounts = Transfer {
+                    from: ctx.accounts.pool_token_account.to_account_info(),
+                    to: ctx.accounts.reporter_token_account.to_account_info(),
+                    authority: ctx.accounts.reward_pool.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, pool_signer);
+                token::transfer(cpi_ctx, bounty)?;
+            }
+        }
+
+        // Now update state (after CPIs are complete). The report account itself is
+        // closed back to the reporter so they can file again against this mint.
+        let pool = &mut ctx.accounts.reward_pool;
+        let reporter_profile = &mut ctx.accounts.reporter_profile;
+
+        if upheld {
+            pool.total_distributed = pool.total_distributed.checked_add(bounty).unwrap();
+            reporter_profile.reward_points = reporter_profile
+                .reward_points
+                .checked_add(SCAM_REPORT_REWARD_POINTS)
+                .unwrap();
+        } else {
+            pool.total_deposited = pool.total_deposited.checked_add(bond_amount).unwrap();
+        }
+
+        emit!(ScamReportResolved {
+            reporter: reporter_key,
+            mint: report_mint,
+            upheld,
+            bond_amount,
+            bounty,
+            resolver: ctx.accounts.moderator.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // ACCOUNT MIGRATION
+    // ============================================
+
+    /// Upgrades a token entry, reward pool, developer registry or project stakes
+    /// account written by the first release: the account is grown to its current
+    /// size and rewritten with the appended fields at their defaults. Anyone can
+    /// pay for the migration; the stored values are carried over unchanged.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+
+        let (discriminator, legacy_len) = {
+            let data = account.try_borrow_data()?;
+            require!(data.len() >= 8, ChainProofError::NotALegacyAccount);
+            (data[..8].to_vec(), data.len())
+        };
+
+        if discriminator == TokenEntry::DISCRIMINATOR {
+            require!(legacy_len == LegacyTokenEntry::LEN, ChainProofError::NotALegacyAccount);
+            let legacy = LegacyTokenEntry::deserialize(&mut &account.try_borrow_data()?[8..])?;
+            let upgraded = TokenEntry {
+                authority: legacy.authority,
+                mint: legacy.mint,
+                name: legacy.name,
+                symbol: legacy.symbol,
+                ipfs_hash: legacy.ipfs_hash,
+                timestamp: legacy.timestamp,
+                bump: legacy.bump,
+                impersonation_risk: false,
+                listing_status: ListingStatus::Pending,
+                listing_reason: ListingReason::None,
+                reviewed_at: 0,
+                symbol_index_page: None,
+                authority_index_page: None,
+            };
+            rewrite_account(&account, &payer, &system_program, TokenEntry::LEN, &upgraded)?;
+        } else if discriminator == RewardPool::DISCRIMINATOR {
+            require!(legacy_len == LegacyRewardPool::LEN, ChainProofError::NotALegacyAccount);
+            let legacy = LegacyRewardPool::deserialize(&mut &account.try_borrow_data()?[8..])?;
+            let upgraded = RewardPool {
+                authority: legacy.authority,
+                total_deposited: legacy.total_deposited,
+                total_distributed: legacy.total_distributed,
+                last_distribution: legacy.last_distribution,
+                distribution_interval: legacy.distribution_interval,
+                developer_share_bps: legacy.developer_share_bps,
+                user_share_bps: legacy.user_share_bps,
+                bump: legacy.bump,
+                pending_authority: None,
+            };
+            rewrite_account(&account, &payer, &system_program, RewardPool::LEN, &upgraded)?;
+        } else if discriminator == DeveloperRegistry::DISCRIMINATOR {
+            require!(
+                legacy_len == LegacyDeveloperRegistry::LEN,
+                ChainProofError::NotALegacyAccount
+            );
+            let legacy = LegacyDeveloperRegistry::deserialize(&mut &account.try_borrow_data()?[8..])?;
+            let upgraded = DeveloperRegistry {
+                authority: legacy.authority,
+                total_developers: legacy.total_developers,
+                bump: legacy.bump,
+                pending_authority: None,
+            };
+            rewrite_account(&account, &payer, &system_program, DeveloperRegistry::LEN, &upgraded)?;
+        } else if discriminator == ProjectStakes::DISCRIMINATOR {
+            require!(legacy_len == LegacyProjectStakes::LEN, ChainProofError::NotALegacyAccount);
+            let legacy = LegacyProjectStakes::deserialize(&mut &account.try_borrow_data()?[8..])?;
+            // Totals are rebuilt as each position goes through migrate_user_stake (the old
+            // total_stakes counted every top-up), and the level is recomputed on the next refresh
+            let upgraded = ProjectStakes {
+                project_mint: legacy.project_mint,
+                total_stakes: 0,
+                verification_level: VerificationLevel::None,
+                bump: legacy.bump,
+                unique_stakers: 0,
+                total_staked: 0,
+                total_pending_unstake: 0,
+                reward_per_share: 0,
+                undistributed_rewards: 0,
+                status: ProjectStatus::Active,
+                flagged_at: 0,
+                flag_cleared_at: 0,
+                slash_rate_bps: 0,
+                total_reward_weight: 0,
+                total_challenged: 0,
+                unique_challengers: 0,
+                challenger_rewards: 0,
+                reward_streams: Vec::new(),
+            };
+            rewrite_account(&account, &payer, &system_program, ProjectStakes::LEN, &upgraded)?;
+        } else {
+            return err!(ChainProofError::NotALegacyAccount);
+        }
+
+        emit!(AccountMigrated {
+            account: account.key(),
+            payer: payer.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Upgrades a stake position written by the first release and adds it to its
+    /// project's totals. The project stakes account has to be migrated first.
+    pub fn migrate_user_stake(ctx: Context<MigrateUserStake>) -> Result<()> {
+        let account = ctx.accounts.user_stake.to_account_info();
+        let project_stakes = &mut ctx.accounts.project_stakes;
+        let clock = Clock::get()?;
+
+        let legacy = {
+            let data = account.try_borrow_data()?;
+            require!(
+                data.len() == LegacyUserStake::LEN && data[..8] == *UserStake::DISCRIMINATOR,
+                ChainProofError::NotALegacyAccount
+            );
+            LegacyUserStake::deserialize(&mut &data[8..])?
+        };
+        require!(
+            legacy.project_mint == project_stakes.project_mint,
+            ChainProofError::ProjectMismatch
+        );
+
+        // The old layout implies the position was empty before staked_at and, with a
+        // request pending, held its whole amount until the request
+        let mut checkpoints = vec![ActiveCheckpoint {
+            changed_at: legacy.staked_at,
+            active_before: 0,
+        }];
+        if let Some(requested_at) = legacy.unstake_requested_at {
+            checkpoints.push(ActiveCheckpoint {
+                changed_at: requested_at,
+                active_before: legacy.amount,
+            });
+        }
+        let mut active_history = [ActiveCheckpoint::default(); ACTIVE_HISTORY_LEN];
+        active_history[ACTIVE_HISTORY_LEN - checkpoints.len()..].copy_from_slice(&checkpoints);
+
+        // A request under the old layout always covered the whole position
+        let mut user_stake = UserStake {
+            user: legacy.user,
+            project_mint: legacy.project_mint,
+            amount: legacy.amount,
+            staked_at: legacy.staked_at,
+            unstake_requested_at: legacy.unstake_requested_at,
+            bump: legacy.bump,
+            pending_unstake_amount: if legacy.unstake_requested_at.is_some() {
+                legacy.amount
+            } else {
+                0
+            },
+            reward_debt: 0,
+            pending_rewards: 0,
+            sponsored_rewards: Default::default(),
+            slash_applied: false,
+            lockup: LockupTier::None,
+            unlock_at: 0,
+            receipt_balance: 0,
+            active_history,
+            unstake_available_at: legacy.unstake_requested_at.map_or(0, |requested_at| {
+                requested_at
+                    .checked_add(ctx.accounts.protocol_config.unstake_cooldown)
+                    .unwrap()
+            }),
+            // The first release credited the raw amount
+            reward_points: legacy.amount,
+        };
+
+        project_stakes.accrue_reward_streams(clock.unix_timestamp);
+        if user_stake.amount > 0 {
+            project_stakes.total_stakes = project_stakes.total_stakes.checked_add(1).unwrap();
+        }
+        project_stakes.apply_position_change(0, user_stake.active_amount());
+        project_stakes.apply_weight_change(0, user_stake.reward_weight());
+        project_stakes.total_pending_unstake = project_stakes
+            .total_pending_unstake
+            .checked_add(user_stake.pending_unstake_amount)
+            .unwrap();
+        user_stake.checkpoint_rewards(project_stakes);
+
+        rewrite_account(
+            &account,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            UserStake::LEN,
+            &user_stake,
+        )?;
+
+        emit!(AccountMigrated {
+            account: account.key(),
+            payer: ctx.accounts.payer.key(),
+        });
+
+        Ok(())
+    }
+}
+
+// ============================================
+// HELPERS
+// ============================================
+
+/// Normalizes a symbol for the impersonation index: separators are dropped,
+/// common look-alike characters are folded to Latin and the result is uppercased.
+pub fn normalize_symbol(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '.' | '-' | '_'))
+        .map(fold_homoglyph)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// PDA seed for the index accounts of a symbol.
+pub fn symbol_seed(symbol: &str) -> Vec<u8> {
+    normalize_symbol(symbol).into_bytes()
+}
+
+/// `HOMOGLYPHS` in scripts/registerToken.js copies this table so clients derive
+/// the same symbol index PDAs; the tests check the two stay in step.
+fn fold_homoglyph(c: char) -> char {
+    match c {
+        '0' | 'О' | 'о' | 'Ο' | 'ο' => 'O',
+        '1' | 'l' | '|' | '!' | 'І' | 'і' | 'Ι' => 'I',
+        '5' | '$' | 'Ѕ' | 'ѕ' => 'S',
+        'А' | 'а' | 'Α' => 'A',
+        'В' | 'в' | 'Β' => 'B',
+        'С' | 'с' | 'Ϲ' | 'ϲ' => 'C',
+        'Е' | 'е' | 'Ε' => 'E',
+        'Н' | 'н' | 'Η' => 'H',
+        'Ј' | 'ј' => 'J',
+        'К' | 'к' | 'Κ' | 'κ' => 'K',
+        'М' | 'м' | 'Μ' => 'M',
+        'Ν' => 'N',
+        'Р' | 'р' | 'Ρ' => 'P',
+        'Т' | 'т' | 'Τ' | 'τ' => 'T',
+        'Х' | 'х' | 'Χ' => 'X',
+        'У' | 'у' | 'Υ' => 'Y',
+        'Ζ' => 'Z',
+        _ => c,
+    }
+}
+
+/// Reallocs a program-owned account up to `new_len`, topping up rent from `payer`.
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if new_len <= account.data_len() {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?;
+    let shortfall = rent.minimum_balance(new_len).saturating_sub(account.lamports());
+    if shortfall > 0 {
+        let cpi_accounts = system_program::Transfer {
+            from: payer.clone(),
+            to: account.clone(),
+        };
+        let cpi_ctx = CpiContext::new(system_program.clone(), cpi_accounts);
+        system_program::transfer(cpi_ctx, shortfall)?;
+    }
+
+    account.resize(new_len)?;
+    Ok(())
+}
+
+/// Grows a legacy account to `new_len` and rewrites it in the current layout.
+fn rewrite_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+    upgraded: &T,
+) -> Result<()> {
+    grow_account(account, payer, system_program, new_len)?;
+
+    // Shorter strings or options may have left stale bytes behind the old fields
+    let mut data = account.try_borrow_mut_data()?;
+    data.fill(0);
+    upgraded.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+/// Fills in a symbol index header the first time it's created.
+fn init_symbol_index(index: &mut SymbolIndex, symbol: &str, bump: u8) {
+    if index.symbol.is_empty() {
+        index.symbol = normalize_symbol(symbol);
+        index.verified_mint = None;
+        index.open_page = 0;
+        index.bump = bump;
+    }
+}
+
+/// Records `mint` on the open page of a symbol index, moving the index on to a
+/// fresh page once this one fills up. Returns the page the mint was written to.
+fn add_to_symbol_index<'info>(
+    index: &mut SymbolIndex,
+    page: &mut Account<'info, SymbolIndexPage>,
+    page_bump: u8,
+    mint: Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u32> {
+    if page.symbol.is_empty() {
+        page.symbol = index.symbol.clone();
+        page.page = index.open_page;
+        page.bump = page_bump;
+    }
+
+    grow_account(
+        &page.to_account_info(),
+        payer,
+        system_program,
+        SymbolIndexPage::space(page.mints.len() + 1),
+    )?;
+    page.mints.push(mint);
+
+    if page.mints.len() >= SYMBOL_INDEX_PAGE_SIZE {
+        index.open_page = index.open_page.checked_add(1).unwrap();
+    }
+
+    Ok(page.page)
+}
+
+/// Drops a token entry from its symbol index. Entries registered before the
+/// index existed have no page, and only the verified claim is cleared for them.
+fn remove_from_symbol_index(
+    index: &mut SymbolIndex,
+    page: Option<&mut SymbolIndexPage>,
+    token_entry: &TokenEntry,
+) -> Result<()> {
+    if token_entry.symbol_index_page.is_some() {
+        let page = page.ok_or(ChainProofError::MissingIndexPage)?;
+        if let Some(position) = page.mints.iter().position(|m| *m == token_entry.mint) {
+            page.mints.swap_remove(position);
+        }
+    }
+
+    if index.verified_mint == Some(token_entry.mint) {
+        index.verified_mint = None;
+    }
+
+    Ok(())
+}
+
+/// Fills in an authority index header the first time it's created.
+fn init_authority_index(index: &mut AuthorityIndex, authority: Pubkey, bump: u8) {
+    if index.authority == Pubkey::default() {
+        index.authority = authority;
+        index.open_page = 0;
+        index.bump = bump;
+    }
+}
+
+/// Records `mint` on the open page of an authority's index, moving the index on
+/// to a fresh page once this one fills up. Returns the page the mint was written to.
+fn add_to_authority_index<'info>(
+    index: &mut AuthorityIndex,
+    page: &mut Account<'info, AuthorityIndexPage>,
+    page_bump: u8,
+    mint: Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u32> {
+    if page.authority == Pubkey::default() {
+        page.authority = index.authority;
+        page.page = index.open_page;
+        page.bump = page_bump;
+    }
+
+    grow_account(
+        &page.to_account_info(),
+        payer,
+        system_program,
+        AuthorityIndexPage::space(page.mints.len() + 1),
+    )?;
+    page.mints.push(mint);
+
+    if page.mints.len() >= AUTHORITY_INDEX_PAGE_SIZE {
+        index.open_page = index.open_page.checked_add(1).unwrap();
+    }
+
+    Ok(page.page)
+}
+
+/// Drops a token entry from its authority's index. Entries registered before
+/// the index existed have no page and are left alone.
+fn remove_from_authority_index(
+    page: Option<&mut AuthorityIndexPage>,
+    token_entry: &TokenEntry,
+) -> Result<()> {
+    if token_entry.authority_index_page.is_some() {
+        let page = page.ok_or(ChainProofError::MissingIndexPage)?;
+        if let Some(position) = page.mints.iter().position(|m| *m == token_entry.mint) {
+            page.mints.swap_remove(position);
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies a validated parameter change to the protocol singletons.
+fn apply_parameter_change(
+    change: &ParameterChange,
+    reward_pool: &mut RewardPool,
+    config: &mut ProtocolConfig,
+    developer_registry: &mut DeveloperRegistry,
+) -> Result<()> {
+    change.validate()?;
+
+    match *change {
+        ParameterChange::RewardShares {
+            developer_share_bps,
+            user_share_bps,
+        } => {
+            reward_pool.developer_share_bps = developer_share_bps;
+            reward_pool.user_share_bps = user_share_bps;
+        }
+        ParameterChange::DistributionInterval { interval } => {
+            reward_pool.distribution_interval = interval;
+        }
+        ParameterChange::AdminTimelock { delay } => {
+            config.admin_timelock = delay;
+        }
+        ParameterChange::VerificationTiers { tiers } => {
+            config.verification_tiers = tiers;
+        }
+        ParameterChange::MaxVerifiedRiskScore { max_risk_score } => {
+            config.max_verified_risk_score = max_risk_score;
+        }
+        ParameterChange::UnstakeCooldown { cooldown } => {
+            config.unstake_cooldown = cooldown;
+        }
+        ParameterChange::SlashRate { rate_bps } => {
+            config.slash_rate_bps = rate_bps;
+        }
+        ParameterChange::ChallengeTerms {
+            dispute_window,
+            penalty_bps,
+            min_age,
+        } => {
+            config.challenge_dispute_window = dispute_window;
+            config.challenge_penalty_bps = penalty_bps;
+            config.challenge_min_age = min_age;
+        }
+        ParameterChange::EmergencyUnstakePenalty { penalty_bps } => {
+            config.emergency_unstake_penalty_bps = penalty_bps;
+        }
+        ParameterChange::SlashDestination { destination } => {
+            config.slash_destination = destination;
+        }
+        ParameterChange::RewardPoolAuthority { new_authority } => {
+            require!(new_authority != reward_pool.authority, ChainProofError::InvalidAuthority);
+            reward_pool.pending_authority = Some(new_authority);
+        }
+        ParameterChange::DeveloperRegistryAuthority { new_authority } => {
+            require!(
+                new_authority != developer_registry.authority,
+                ChainProofError::InvalidAuthority
+            );
+            developer_registry.pending_authority = Some(new_authority);
+        }
+    }
+
+    Ok(())
+}
+
+/// Amount left after slashing `rate_bps` of it.
+fn apply_slash_rate(amount: u64, rate_bps: u16) -> u64 {
+    (amount as u128 * (10000 - rate_bps as u128) / 10000) as u64
+}
+
+/// Marks a flagged project slashed at `rate_bps` and scales its totals, returning the
+/// amount to take out of the stake vault. Each UserStake is scaled lazily on its next
+/// withdrawal step; rounding there only ever leaves positions with less than the vault keeps.
+fn slash_project_stakes(project_stakes: &mut ProjectStakes, rate_bps: u16, to_challengers: bool) -> u64 {
+    let staked_balance = project_stakes
+        .total_staked
+        .checked_add(project_stakes.total_pending_unstake)
+        .unwrap();
+    let slashed_amount = staked_balance - apply_slash_rate(staked_balance, rate_bps);
+
+    project_stakes.status = ProjectStatus::Slashed;
+    project_stakes.slash_rate_bps = rate_bps;
+    project_stakes.total_staked = apply_slash_rate(project_stakes.total_staked, rate_bps);
+    project_stakes.total_pending_unstake = apply_slash_rate(project_stakes.total_pending_unstake, rate_bps);
+    project_stakes.total_reward_weight = apply_slash_rate(project_stakes.total_reward_weight, rate_bps);
+    if to_challengers {
+        project_stakes.challenger_rewards = slashed_amount;
+    }
+
+    slashed_amount
+}
+
+/// Drops a lockup that has run out so the position earns at the base rate again.
+/// Settle rewards first. Returns whether the lockup was dropped.
+fn release_expired_lockup(user_stake: &mut UserStake, project_stakes: &mut ProjectStakes, now: i64) -> bool {
+    if user_stake.lockup == LockupTier::None || now < user_stake.unlock_at {
+        return false;
+    }
+
+    let previous_weight = user_stake.reward_weight();
+    user_stake.lockup = LockupTier::None;
+    user_stake.checkpoint_rewards(project_stakes);
+    project_stakes.apply_weight_change(previous_weight, user_stake.reward_weight());
+
+    true
+}
+
+/// Settles a position up to `now` and takes its pending base rewards, dropping a lockup
+/// that has run out. Streams are accrued first so the time before the release is paid
+/// at the locked weight.
+fn take_stake_rewards(user_stake: &mut UserStake, project_stakes: &mut ProjectStakes, now: i64) -> Result<u64> {
+    project_stakes.accrue_reward_streams(now);
+    user_stake.settle_rewards(project_stakes);
+    user_stake.apply_slash(project_stakes);
+    user_stake.checkpoint_rewards(project_stakes);
+    release_expired_lockup(user_stake, project_stakes, now);
+
+    let amount = user_stake.pending_rewards;
+    require!(amount > 0, ChainProofError::NoRewardsToClaim);
+    user_stake.pending_rewards = 0;
+
+    Ok(amount)
+}
+
+/// Adds `amount`, already moved into the project's stake vault, to a position and
+/// updates the project totals and the staker's profile. Receipts and the verification
+/// level are left to the caller.
+fn record_stake(
+    project_stakes: &mut ProjectStakes,
+    user_stake: &mut UserStake,
+    user_profile: &mut UserProfile,
+    amount: u64,
+    lockup: LockupTier,
+    now: i64,
+) -> Result<()> {
+    require!(amount > 0, ChainProofError::InvalidStakeAmount);
+    require!(
+        project_stakes.status == ProjectStatus::Active,
+        ChainProofError::ProjectNotActive
+    );
+
+    project_stakes.accrue_reward_streams(now);
+    user_stake.settle_rewards(project_stakes);
+    release_expired_lockup(user_stake, project_stakes, now);
+    let new_position = user_stake.amount == 0;
+    let previous_active = user_stake.active_amount();
+    let previous_weight = user_stake.reward_weight();
+    user_stake.amount = user_stake.amount.checked_add(amount).unwrap();
+    user_stake.staked_at = now;
+
+    // A lockup covers the whole position and can only be extended while it runs
+    if lockup >= user_stake.lockup || now >= user_stake.unlock_at {
+        user_stake.lockup = lockup;
+    }
+    user_stake.unlock_at = user_stake.unlock_at.max(now + lockup.duration());
+    user_stake.checkpoint_rewards(project_stakes);
+
+    // Update project stakes; total_stakes counts positions, not top-ups
+    if new_position {
+        project_stakes.total_stakes = project_stakes.total_stakes.checked_add(1).unwrap();
+    }
+    user_stake.record_active_change(previous_active, now);
+    project_stakes.apply_position_change(previous_active, user_stake.active_amount());
+    project_stakes.apply_weight_change(previous_weight, user_stake.reward_weight());
+
+    // Update user profile
+    let points = user_stake.lockup.apply_multiplier(amount);
+    user_stake.reward_points = user_stake.reward_points.checked_add(points).unwrap();
+    if new_position {
+        user_profile.total_stakes = user_profile.total_stakes.checked_add(1).unwrap();
+    }
+    user_profile.reward_points = user_profile.reward_points.checked_add(points).unwrap();
+
+    emit!(Staked {
+        user: user_stake.user,
+        project_mint: user_stake.project_mint,
+        amount,
+        total_stakes: project_stakes.total_stakes,
+        unique_stakers: project_stakes.unique_stakers,
+        total_staked: project_stakes.total_staked,
+        lockup: user_stake.lockup,
+        unlock_at: user_stake.unlock_at,
+    });
+
+    Ok(())
+}
+
+/// Opens an unstake request for `amount` of a position. The pending amount stops
+/// counting toward verification and earning rewards straight away, and the lockup
+/// ends, so what stays staked earns at the base rate. The verification level is left
+/// to the caller.
+fn open_unstake_request(
+    user_stake: &mut UserStake,
+    project_stakes: &mut ProjectStakes,
+    amount: u64,
+    cooldown: i64,
+    now: i64,
+) -> Result<()> {
+    require!(
+        project_stakes.status != ProjectStatus::Flagged,
+        ChainProofError::ProjectFlagged
+    );
+    require!(
+        user_stake.unstake_requested_at.is_none(),
+        ChainProofError::UnstakeAlreadyRequested
+    );
+
+    project_stakes.accrue_reward_streams(now);
+    user_stake.settle_rewards(project_stakes);
+    user_stake.apply_slash(project_stakes);
+
+    require!(user_stake.amount > 0, ChainProofError::NoStakeFound);
+    require!(
+        amount > 0 && amount <= user_stake.amount,
+        ChainProofError::InvalidUnstakeAmount
+    );
+    require!(now >= user_stake.unlock_at, ChainProofError::StakeLocked);
+
+    let previous_weight = user_stake.reward_weight();
+    user_stake.unstake_requested_at = Some(now);
+    user_stake.unstake_available_at = now.checked_add(cooldown).unwrap();
+    user_stake.pending_unstake_amount = amount;
+    user_stake.lockup = LockupTier::None;
+    user_stake.checkpoint_rewards(project_stakes);
+
+    let previous_active = user_stake.amount;
+    user_stake.record_active_change(previous_active, now);
+    project_stakes.apply_position_change(previous_active, user_stake.active_amount());
+    project_stakes.apply_weight_change(previous_weight, user_stake.reward_weight());
+    project_stakes.total_pending_unstake = project_stakes.total_pending_unstake.checked_add(amount).unwrap();
+
+    Ok(())
+}
+
+/// Opens the request an emergency unstake withdraws straight away. A request that is
+/// already open is withdrawn as it stands, so `amount` has to match it.
+fn open_emergency_request(
+    user_stake: &mut UserStake,
+    project_stakes: &mut ProjectStakes,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    if user_stake.unstake_requested_at.is_some() {
+        require!(
+            amount == user_stake.pending_unstake_amount,
+            ChainProofError::InvalidUnstakeAmount
+        );
+        return Ok(());
+    }
+
+    open_unstake_request(user_stake, project_stakes, amount, 0, now)
+}
+
+/// What left a position when part of it moved elsewhere.
+struct MovedStake {
+    receipts: u64,
+    remaining: u64,
+}
+
+/// Takes `amount` of unlocked active stake out of a position, along with the receipts
+/// and reward points that back it, and updates the project and profile counters. The
+/// position's lockup ends. Moving the tokens and the verification level are left to the
+/// caller; the points come back from record_stake at the destination's multiplier.
+fn move_out_of_position(
+    user_stake: &mut UserStake,
+    project_stakes: &mut ProjectStakes,
+    user_profile: &mut UserProfile,
+    amount: u64,
+    now: i64,
+) -> Result<MovedStake> {
+    require!(
+        amount <= user_stake.active_amount(),
+        ChainProofError::InvalidUnstakeAmount
+    );
+    require!(now >= user_stake.unlock_at, ChainProofError::StakeLocked);
+
+    project_stakes.accrue_reward_streams(now);
+    user_stake.settle_rewards(project_stakes);
+    let previous_active = user_stake.active_amount();
+    let previous_weight = user_stake.reward_weight();
+    let receipts = user_stake.receipts_for(amount);
+    let points = user_stake.reward_points_for(amount);
+    user_stake.receipt_balance = user_stake
+        .receipt_balance
+        .checked_sub(receipts)
+        .ok_or(ChainProofError::AccountingUnderflow)?;
+    user_stake.reward_points = user_stake
+        .reward_points
+        .checked_sub(points)
+        .ok_or(ChainProofError::AccountingUnderflow)?;
+    user_stake.amount = user_stake.amount.checked_sub(amount).unwrap();
+    user_stake.lockup = LockupTier::None;
+    user_stake.checkpoint_rewards(project_stakes);
+
+    user_stake.record_active_change(previous_active, now);
+    project_stakes.apply_position_change(previous_active, user_stake.active_amount());
+    project_stakes.apply_weight_change(previous_weight, user_stake.reward_weight());
+
+    let remaining = user_stake.amount;
+    user_profile.reward_points = user_profile.reward_points.saturating_sub(points);
+    if remaining == 0 {
+        project_stakes.total_stakes = project_stakes.total_stakes.saturating_sub(1);
+        user_profile.total_stakes = user_profile.total_stakes.saturating_sub(1);
+    }
+
+    Ok(MovedStake { receipts, remaining })
+}
+
+/// Takes the active stake that `receipts` stand for out of a position, at the position's
+/// own receipt rate, and returns the amount. The position gives up exactly `receipts`;
+/// rounding goes its way. Burning the receipts and restaking the amount are left to the caller.
+fn redeem_from_position(
+    user_stake: &mut UserStake,
+    project_stakes: &mut ProjectStakes,
+    user_profile: &mut UserProfile,
+    receipts: u64,
+    now: i64,
+) -> Result<u64> {
+    require!(
+        receipts > 0 && receipts <= user_stake.receipt_balance,
+        ChainProofError::InvalidRedemption
+    );
+    let amount = (user_stake.amount as u128 * receipts as u128 / user_stake.receipt_balance as u128) as u64;
+    require!(amount > 0, ChainProofError::InvalidRedemption);
+
+    let moved = move_out_of_position(user_stake, project_stakes, user_profile, amount, now)?;
+    user_stake.receipt_balance = user_stake
+        .receipt_balance
+        .checked_sub(receipts - moved.receipts)
+        .ok_or(ChainProofError::AccountingUnderflow)?;
+
+    Ok(amount)
+}
+
+/// Puts a position's pending unstake back to work, so it counts toward verification
+/// and earns rewards again. Returns the amount that was pending. The verification level
+/// is left to the caller.
+fn cancel_unstake_request(user_stake: &mut UserStake, project_stakes: &mut ProjectStakes, now: i64) -> Result<u64> {
+    require!(
+        user_stake.unstake_requested_at.is_some(),
+        ChainProofError::UnstakeNotRequested
+    );
+    require!(
+        project_stakes.status != ProjectStatus::Flagged,
+        ChainProofError::ProjectFlagged
+    );
+
+    project_stakes.accrue_reward_streams(now);
+    user_stake.settle_rewards(project_stakes);
+    user_stake.apply_slash(project_stakes);
+    release_expired_lockup(user_stake, project_stakes, now);
+    let amount = user_stake.pending_unstake_amount;
+    let previous_active = user_stake.active_amount();
+    let previous_weight = user_stake.reward_weight();
+
+    user_stake.pending_unstake_amount = 0;
+    user_stake.unstake_requested_at = None;
+    user_stake.unstake_available_at = 0;
+    user_stake.checkpoint_rewards(project_stakes);
+
+    user_stake.record_active_change(previous_active, now);
+    project_stakes.apply_position_change(previous_active, user_stake.active_amount());
+    project_stakes.apply_weight_change(previous_weight, user_stake.reward_weight());
+    project_stakes.total_pending_unstake = project_stakes.total_pending_unstake.saturating_sub(amount);
+
+    Ok(amount)
+}
+
+/// What a withdrawal took out of a position.
+struct Withdrawal {
+    amount: u64,
+    remaining: u64,
+    penalty: u64,
+    receipts_burned: u64,
+}
+
+/// Takes the pending unstake out of a position, charging `penalty_bps` of it, and
+/// updates the project and profile counters. A slashed project only pays out what is
+/// left of the position. Moving the tokens and receipts is left to the caller.
+fn settle_withdrawal(
+    user_stake: &mut UserStake,
+    project_stakes: &mut ProjectStakes,
+    user_profile: &mut UserProfile,
+    penalty_bps: u16,
+) -> Result<Withdrawal> {
+    user_stake.settle_rewards(project_stakes);
+    user_stake.apply_slash(project_stakes);
+    user_stake.checkpoint_rewards(project_stakes);
+
+    // Only the requested portion is withdrawn; the rest stays staked
+    let amount = user_stake.pending_unstake_amount;
+    let remaining = user_stake.amount.checked_sub(amount).unwrap();
+    let penalty = (amount as u128 * penalty_bps as u128 / 10000) as u64;
+
+    let receipts_burned = user_stake.receipts_for(amount);
+    let points = user_stake.reward_points_for(amount);
+    user_stake.receipt_balance = user_stake
+        .receipt_balance
+        .checked_sub(receipts_burned)
+        .ok_or(ChainProofError::AccountingUnderflow)?;
+    user_stake.reward_points = user_stake
+        .reward_points
+        .checked_sub(points)
+        .ok_or(ChainProofError::AccountingUnderflow)?;
+
+    // The pending amount already left the active totals at request time
+    project_stakes.total_pending_unstake = project_stakes.total_pending_unstake.saturating_sub(amount);
+    if remaining == 0 {
+        project_stakes.total_stakes = project_stakes.total_stakes.saturating_sub(1);
+        user_profile.total_stakes = user_profile.total_stakes.saturating_sub(1);
+    }
+    user_profile.reward_points = user_profile.reward_points.saturating_sub(points);
+
+    // Clear the request and keep any remaining stake active
+    user_stake.amount = remaining;
+    user_stake.pending_unstake_amount = 0;
+    user_stake.unstake_requested_at = None;
+    user_stake.unstake_available_at = 0;
+
+    Ok(Withdrawal {
+        amount,
+        remaining,
+        penalty,
+        receipts_burned,
+    })
+}
+
+/// How a withdrawn challenge is split up.
+struct ChallengePayout {
+    payout: u64,
+    penalty: u64,
+    forfeited: u64,
+}
+
+/// Works out what a challenger gets back when withdrawing at `now`. Moving the tokens
+/// and updating the project totals is left to the caller.
+fn challenge_payout(
+    challenge_stake: &ChallengeStake,
+    project_stakes: &ProjectStakes,
+    config: &ProtocolConfig,
+    now: i64,
+) -> Result<ChallengePayout> {
+    let amount = challenge_stake.amount;
+
+    match project_stakes.status {
+        ProjectStatus::Flagged => err!(ChainProofError::ProjectFlagged),
+        // Confirmed rug: principal plus a pro rata share of the slashed backer stake.
+        // Challenges opened shortly before the flag only get their principal back, so
+        // watching for a moderator's flag and front-running it doesn't pay.
+        ProjectStatus::Slashed => {
+            let share = (project_stakes.challenger_rewards as u128 * amount as u128
+                / project_stakes.total_challenged as u128) as u64;
+            let eligible = challenge_stake.staked_at + config.challenge_min_age <= project_stakes.flagged_at;
+            if eligible {
+                Ok(ChallengePayout {
+                    payout: amount.checked_add(share).unwrap(),
+                    penalty: 0,
+                    forfeited: 0,
+                })
+            } else {
+                Ok(ChallengePayout {
+                    payout: amount,
+                    penalty: 0,
+                    forfeited: share,
+                })
+            }
+        }
+        // Active: withdrawable once the dispute window has passed. Only a flag that a
+        // moderator cleared after the window costs the challenger part of the stake,
+        // which goes to backers; an unflagged project returns the full principal.
+        ProjectStatus::Active => {
+            let window_end = challenge_stake.staked_at + config.challenge_dispute_window;
+            require!(now >= window_end, ChainProofError::DisputeWindowOpen);
+
+            let cleared = project_stakes.flag_cleared_at != 0 && project_stakes.flag_cleared_at >= window_end;
+            let penalty = if cleared {
+                (amount as u128 * config.challenge_penalty_bps as u128 / 10000) as u64
+            } else {
+                0
+            };
+            Ok(ChallengePayout {
+                payout: amount - penalty,
+                penalty,
+                forfeited: 0,
+            })
+        }
+    }
+}
+
+/// Mints liquid stake receipts, signed by the receipt mint authority PDA.
+fn mint_receipts<'info>(
+    receipt_mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    mint_authority: &AccountInfo<'info>,
+    mint_authority_bump: u8,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[b"receipt_mint_authority".as_ref(), &[mint_authority_bump]];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = MintTo {
+        mint: receipt_mint.clone(),
+        to: to.clone(),
+        authority: mint_authority.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer);
+    token::mint_to(cpi_ctx, amount)
+}
+
+/// Burns stake receipts returned by their owner.
+fn burn_receipts<'info>(
+    receipt_mint: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = Burn {
+        mint: receipt_mint.clone(),
+        from: from.clone(),
+        authority: owner.clone(),
+    };
+    let cpi_ctx = CpiContext::new(token_program.clone(), cpi_accounts);
+    token::burn(cpi_ctx, amount)
+}
+
+/// Reads a `ProjectStakes` passed through remaining_accounts, checking its PDA.
+fn load_project_stakes(info: &AccountInfo, project_mint: &Pubkey) -> Result<ProjectStakes> {
+    require!(info.is_writable, ChainProofError::InvalidStakeAccount);
+    read_project_stakes(info, project_mint)
+}
+
+/// Same as `load_project_stakes` for accounts that are only read.
+fn read_project_stakes(info: &AccountInfo, project_mint: &Pubkey) -> Result<ProjectStakes> {
+    require!(info.owner == &crate::ID, ChainProofError::InvalidStakeAccount);
+
+    let project_stakes = ProjectStakes::try_deserialize(&mut &info.data.borrow()[..])?;
+    let expected = Pubkey::create_program_address(
+        &[b"project_stakes", project_mint.as_ref(), &[project_stakes.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| error!(ChainProofError::InvalidStakeAccount))?;
+    require_keys_eq!(info.key(), expected, ChainProofError::InvalidStakeAccount);
+
+    Ok(project_stakes)
+}
+
+/// Reads a project's risk attestation PDA, whose seeds the caller has checked. An
+/// account without data means the project was never attested.
+fn read_risk_attestation(info: &AccountInfo) -> Result<Option<RiskAttestation>> {
+    if info.data_is_empty() {
+        return Ok(None);
+    }
+    require!(info.owner == &crate::ID, ChainProofError::InvalidAttestationAccount);
+
+    Ok(Some(RiskAttestation::try_deserialize(&mut &info.data.borrow()[..])?))
+}
+
+/// Reads the project's `RiskAttestation` PDA passed through remaining_accounts. It is
+/// always passed, and counts as missing only while the PDA holds no data.
+fn load_risk_attestation(info: &AccountInfo, project_mint: &Pubkey) -> Result<Option<RiskAttestation>> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"risk_attestation", project_mint.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(info.key(), expected, ChainProofError::InvalidAttestationAccount);
+
+    read_risk_attestation(info)
+}
+
+/// Reads the user's `UserStake` PDA for `project_mint`, creating the account if it
+/// doesn't exist yet.
+fn load_or_init_user_stake<'info>(
+    info: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    project_mint: &Pubkey,
+    system_program: &AccountInfo<'info>,
+) -> Result<UserStake> {
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"user_stake", user.key.as_ref(), project_mint.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(info.key(), expected, ChainProofError::InvalidStakeAccount);
+    require!(info.is_writable, ChainProofError::InvalidStakeAccount);
+
+    if info.owner == &crate::ID {
+        return UserStake::try_deserialize(&mut &info.data.borrow()[..]);
+    }
+
+    let seeds: &[&[u8]] = &[b"user_stake", user.key.as_ref(), project_mint.as_ref(), &[bump]];
+    let rent = Rent::get()?.minimum_balance(UserStake::LEN);
+    if info.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: user.clone(),
+                    to: info.clone(),
+                },
+                &[seeds],
+            ),
+            rent,
+            UserStake::LEN as u64,
+            &crate::ID,
+        )?;
+    } else {
+        // Anyone can send lamports to the address ahead of time, which makes
+        // create_account fail; top it up to rent exemption and claim it instead
+        let shortfall = rent.saturating_sub(info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: user.clone(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: info.clone(),
+                },
+                &[seeds],
+            ),
+            UserStake::LEN as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: info.clone(),
+                },
+                &[seeds],
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    Ok(UserStake {
+        user: user.key(),
+        project_mint: *project_mint,
+        amount: 0,
+        staked_at: 0,
+        unstake_requested_at: None,
+        pending_unstake_amount: 0,
+        reward_debt: 0,
+        pending_rewards: 0,
+        sponsored_rewards: [StreamCheckpoint::default(); MAX_REWARD_STREAMS],
+        slash_applied: false,
+        lockup: LockupTier::None,
+        unlock_at: 0,
+        receipt_balance: 0,
+        active_history: Default::default(),
+        unstake_available_at: 0,
+        reward_points: 0,
+        bump,
+    })
+}
+
+/// Sums the active stake held by `owner` across the given `(UserStake, ProjectStakes)`
+/// account pairs, net of any slash not yet applied to the position. With a snapshot,
+/// each position counts what it held at that time.
+fn governance_weight(stake_accounts: &[AccountInfo], owner: &Pubkey, snapshot: Option<i64>) -> Result<u64> {
+    let pairs = stake_accounts.chunks_exact(2);
+    require!(pairs.remainder().is_empty(), ChainProofError::InvalidBatch);
+
+    let mut seen: Vec<Pubkey> = Vec::with_capacity(stake_accounts.len() / 2);
+    let mut weight: u64 = 0;
+
+    for pair in pairs {
+        let info = &pair[0];
+        require!(info.owner == &crate::ID, ChainProofError::InvalidStakeAccount);
+        require!(!seen.contains(info.key), ChainProofError::DuplicateStakeAccount);
+        seen.push(info.key());
+
+        let user_stake = UserStake::try_deserialize(&mut &info.data.borrow()[..])?;
+        require!(user_stake.user == *owner, ChainProofError::InvalidStakeAccount);
+        let project_stakes = read_project_stakes(&pair[1], &user_stake.project_mint)?;
+
+        let amount = match snapshot {
+            Some(at) => user_stake.active_amount_at(at),
+            None => user_stake.active_amount(),
+        };
+        let amount = if project_stakes.status == ProjectStatus::Slashed && !user_stake.slash_applied {
+            apply_slash_rate(amount, project_stakes.slash_rate_bps)
+        } else {
+            amount
+        };
+
+        weight = weight.checked_add(amount).unwrap();
+    }
+
+    Ok(weight)
+}
+
+/// Recomputes a project's verification level and emits an event when it moves.
+/// Any level requires an unexpired attestation within the configured risk limit.
+fn update_verification_level(
+    project_stakes: &mut ProjectStakes,
+    config: &ProtocolConfig,
+    attestation: Option<&RiskAttestation>,
+    now: i64,
+) {
+    let risk_score = attestation
+        .filter(|a| a.expires_at > now)
+        .map_or(MAX_RISK_SCORE, |a| a.risk_score);
+
+    // Flagged and slashed projects can't hold any verification level
+    let new_level = if project_stakes.status == ProjectStatus::Active
+        && risk_score <= config.max_verified_risk_score
+    {
+        project_stakes.compute_verification_level(config, risk_score)
+    } else {
+        VerificationLevel::None
+    };
+
+    if new_level != project_stakes.verification_level {
+        let previous_level = project_stakes.verification_level;
+        project_stakes.verification_level = new_level;
+
+        emit!(VerificationLevelChanged {
+            project_mint: project_stakes.project_mint,
+            previous_level,
+            new_level,
+            unique_stakers: project_stakes.unique_stakers,
+            total_staked: project_stakes.total_staked,
+            risk_score,
+        });
+    }
+}
+
+// ============================================
+// ACCOUNT STRUCTS
+// ============================================
+
+#[account]
+pub struct TokenEntry {
+    pub authority: Pubkey,  // 32
+    pub mint: Pubkey,       // 32
+    pub name: String,       // 4 + 50
+    pub symbol: String,     // 4 + 10
+    pub ipfs_hash: String,  // 4 + 100
+    pub timestamp: i64,     // 8
+    pub bump: u8,           // 1
+    // Fields below were appended after the first release; see migrate_account
+    pub impersonation_risk: bool, // 1
+    pub listing_status: ListingStatus, // 1
+    pub listing_reason: ListingReason, // 1
+    pub reviewed_at: i64,   // 8
+    pub symbol_index_page: Option<u32>, // 1 + 4
+    pub authority_index_page: Option<u32>, // 1 + 4
+}
+
+impl TokenEntry {
+    pub const LEN: usize =
+        8 + 32 + 32 + (4 + 50) + (4 + 10) + (4 + 100) + 8 + 1 + 1 + 1 + 1 + 8 + (1 + 4) + (1 + 4);
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ListingStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Suspended,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ListingReason {
+    None,
+    InsufficientInformation,
+    Impersonation,
+    SuspectedScam,
+    Spam,
+    PolicyViolation,
+    Other,
+}
+
+#[account]
+pub struct SymbolIndex {
+    pub symbol: String,                 // 4 + 10 (normalized)
+    pub verified_mint: Option<Pubkey>,  // 1 + 32
+    pub open_page: u32,                 // 4 (page new registrations go to)
+    pub bump: u8,                       // 1
+}
+
+impl SymbolIndex {
+    pub const LEN: usize = 8 + (4 + 10) + (1 + 32) + 4 + 1;
+}
+
+#[account]
+pub struct SymbolIndexPage {
+    pub symbol: String,             // 4 + 10 (normalized)
+    pub page: u32,                  // 4
+    pub bump: u8,                   // 1
+    pub mints: Vec<Pubkey>,         // 4 + 32 per entry (grows, max SYMBOL_INDEX_PAGE_SIZE)
+}
+
+impl SymbolIndexPage {
+    pub const BASE_LEN: usize = 8 + (4 + 10) + 4 + 1 + 4;
+
+    pub fn space(entries: usize) -> usize {
+        Self::BASE_LEN + 32 * entries
+    }
+}
+
+#[account]
+pub struct AuthorityIndex {
+    pub authority: Pubkey,          // 32
+    pub open_page: u32,             // 4 (page new entries go to)
+    pub bump: u8,                   // 1
+}
+
+impl AuthorityIndex {
+    pub const LEN: usize = 8 + 32 + 4 + 1;
+}
+
+#[account]
+pub struct AuthorityIndexPage {
+    pub authority: Pubkey,          // 32
+    pub page: u32,                  // 4
+    pub bump: u8,                   // 1
+    pub mints: Vec<Pubkey>,         // 4 + 32 per entry (grows, max AUTHORITY_INDEX_PAGE_SIZE)
+}
+
+impl AuthorityIndexPage {
+    pub const BASE_LEN: usize = 8 + 32 + 4 + 1 + 4;
+
+    pub fn space(entries: usize) -> usize {
+        Self::BASE_LEN + 32 * entries
+    }
+}
+
+#[account]
+pub struct RewardPool {
+    pub authority: Pubkey,          // 32
+    pub total_deposited: u64,       // 8
+    pub total_distributed: u64,     // 8
+    pub last_distribution: i64,     // 8
+    pub distribution_interval: i64, // 8
+    pub developer_share_bps: u16,   // 2 (basis points: 6000 = 60%)
+    pub user_share_bps: u16,        // 2
+    pub bump: u8,                   // 1
+    // Fields below were appended after the first release; see migrate_account
+    pub pending_authority: Option<Pubkey>, // 1 + 32
+}
+
+impl RewardPool {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 2 + 2 + 1 + (1 + 32);
+}
+
+#[account]
+pub struct UserProfile {
+    pub wallet: Pubkey,             // 32
+    pub username: String,           // 4 + 32
+    pub referral_code: Option<String>, // 1 + 4 + 32
+    pub is_developer: bool,         // 1
+    pub total_stakes: u64,          // 8
+    pub reward_points: u64,         // 8
+    pub created_at: i64,            // 8
+    pub bump: u8,                   // 1
+}
+
+impl UserProfile {
+    pub const LEN: usize = 8 + 32 + (4 + 32) + (1 + 4 + 32) + 1 + 8 + 8 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    Registry,
+    Staking,
+    PoolDeposits,
+    Distribution,
+    Profiles,
+}
+
+impl Subsystem {
+    pub fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+#[account]
+pub struct ProtocolConfig {
+    pub paused: u8,                 // 1 (bitmask of Subsystem::mask)
+    pub admin_timelock: i64,        // 8
+    pub next_action_id: u64,        // 8
+    pub next_proposal_id: u64,      // 8
+    pub verification_tiers: [VerificationTier; 3], // 3 * 18 (Bronze, Silver, Gold)
+    pub max_verified_risk_score: u16, // 2
+    pub unstake_cooldown: i64,      // 8
+    pub slash_rate_bps: u16,        // 2 (share of each stake taken on a slash)
+    pub challenge_dispute_window: i64, // 8
+    pub challenge_penalty_bps: u16, // 2
+    pub challenge_min_age: i64,     // 8 (age a challenge needs at flagging to share the slash)
+    pub emergency_unstake_penalty_bps: u16, // 2
+    pub slash_destination: SlashDestination, // 1 (where slashed stake goes without challengers)
+    pub bump: u8,                   // 1
+}
+
+impl ProtocolConfig {
+    pub const LEN: usize = 8 + 1 + 8 + 8 + 8 + VerificationTier::LEN * 3 + 2 + 8 + 2 + 8 + 2 + 8 + 2 + 1 + 1;
+
+    pub fn is_paused(&self, subsystem: Subsystem) -> bool {
+        self.paused & subsystem.mask() != 0
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationTier {
+    pub min_unique_stakers: u64,    // 8
+    pub min_total_stake: u64,       // 8
+    pub max_risk_score: u16,        // 2
+}
+
+impl VerificationTier {
+    pub const LEN: usize = 8 + 8 + 2;
+}
+
+const DEFAULT_VERIFICATION_TIERS: [VerificationTier; 3] = [
+    // Bronze: 10 stakers, 1,000 tokens, within max_verified_risk_score
+    VerificationTier {
+        min_unique_stakers: 10,
+        min_total_stake: 1_000_000_000,
+        max_risk_score: MAX_RISK_SCORE,
+    },
+    // Silver: 25 stakers, 10,000 tokens, at most MODERATE risk
+    VerificationTier {
+        min_unique_stakers: 25,
+        min_total_stake: 10_000_000_000,
+        max_risk_score: 65,
+    },
+    // Gold: 50 stakers, 50,000 tokens, SAFE risk
+    VerificationTier {
+        min_unique_stakers: 50,
+        min_total_stake: 50_000_000_000,
+        max_risk_score: 40,
+    },
+];
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ParameterChange {
+    RewardShares {
+        developer_share_bps: u16,
+        user_share_bps: u16,
+    },
+    DistributionInterval {
+        interval: i64,
+    },
+    AdminTimelock {
+        delay: i64,
+    },
+    VerificationTiers {
+        tiers: [VerificationTier; 3],
+    },
+    MaxVerifiedRiskScore {
+        max_risk_score: u16,
+    },
+    UnstakeCooldown {
+        cooldown: i64,
+    },
+    SlashRate {
+        rate_bps: u16,
+    },
+    ChallengeTerms {
+        dispute_window: i64,
+        penalty_bps: u16,
+        min_age: i64,
+    },
+    EmergencyUnstakePenalty {
+        penalty_bps: u16,
+    },
+    SlashDestination {
+        destination: SlashDestination,
+    },
+    RewardPoolAuthority {
+        new_authority: Pubkey,
+    },
+    DeveloperRegistryAuthority {
+        new_authority: Pubkey,
+    },
+}
+
+impl ParameterChange {
+    pub const MAX_LEN: usize = 1 + VerificationTier::LEN * 3; // variant tag + largest payload
+
+    /// Authority handovers stay with the admin queue and can't be voted through.
+    pub fn is_authority_change(&self) -> bool {
+        matches!(
+            self,
+            ParameterChange::RewardPoolAuthority { .. }
+                | ParameterChange::DeveloperRegistryAuthority { .. }
+        )
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            ParameterChange::RewardShares {
+                developer_share_bps,
+                user_share_bps,
+            } => {
+                require!(
+                    developer_share_bps as u32 + user_share_bps as u32 <= 10000,
+                    ChainProofError::InvalidParameter
+                );
+            }
+            ParameterChange::DistributionInterval { interval } => {
+                require!(interval > 0, ChainProofError::InvalidParameter);
+            }
+            ParameterChange::AdminTimelock { delay } => {
+                require!(delay >= MIN_ADMIN_TIMELOCK, ChainProofError::InvalidParameter);
+            }
+            ParameterChange::VerificationTiers { tiers } => {
+                require!(tiers[0].min_unique_stakers > 0, ChainProofError::InvalidParameter);
+                for tier in tiers.iter() {
+                    require!(tier.max_risk_score <= MAX_RISK_SCORE, ChainProofError::InvalidParameter);
+                }
+                // Each tier must be at least as strict as the one below it
+                for pair in tiers.windows(2) {
+                    require!(
+                        pair[1].min_unique_stakers >= pair[0].min_unique_stakers
+                            && pair[1].min_total_stake >= pair[0].min_total_stake
+                            && pair[1].max_risk_score <= pair[0].max_risk_score,
+                        ChainProofError::InvalidParameter
+                    );
+                }
+            }
+            ParameterChange::MaxVerifiedRiskScore { max_risk_score } => {
+                require!(max_risk_score <= MAX_RISK_SCORE, ChainProofError::InvalidParameter);
+            }
+            ParameterChange::UnstakeCooldown { cooldown } => {
+                require!(
+                    (0..=MAX_UNSTAKE_COOLDOWN).contains(&cooldown),
+                    ChainProofError::InvalidParameter
+                );
+            }
+            ParameterChange::SlashRate { rate_bps } => {
+                require!(rate_bps <= 10000, ChainProofError::InvalidParameter);
+            }
+            ParameterChange::ChallengeTerms {
+                dispute_window,
+                penalty_bps,
+                min_age,
+            } => {
+                require!(
+                    (0..=MAX_CHALLENGE_DISPUTE_WINDOW).contains(&dispute_window)
+                        && (0..=MAX_CHALLENGE_DISPUTE_WINDOW).contains(&min_age)
+                        && penalty_bps <= 10000,
+                    ChainProofError::InvalidParameter
+                );
+            }
+            ParameterChange::EmergencyUnstakePenalty { penalty_bps } => {
+                require!(penalty_bps <= 10000, ChainProofError::InvalidParameter);
+            }
+            ParameterChange::SlashDestination { .. } => {}
+            ParameterChange::RewardPoolAuthority { new_authority }
+            | ParameterChange::DeveloperRegistryAuthority { new_authority } => {
+                require!(new_authority != Pubkey::default(), ChainProofError::InvalidAuthority);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[account]
+pub struct PendingAction {
+    pub action_id: u64,             // 8
+    pub proposer: Pubkey,           // 32
+    pub change: ParameterChange,    // ParameterChange::MAX_LEN
+    pub queued_at: i64,             // 8
+    pub eta: i64,                   // 8
+    pub expires_at: i64,            // 8
+    pub bump: u8,                   // 1
+}
+
+impl PendingAction {
+    pub const LEN: usize = 8 + 8 + 32 + ParameterChange::MAX_LEN + 8 + 8 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    Active,
+    Queued,
+    Defeated,
+}
+
+#[account]
+pub struct Proposal {
+    pub proposal_id: u64,           // 8
+    pub proposer: Pubkey,           // 32
+    pub change: ParameterChange,    // ParameterChange::MAX_LEN
+    pub created_at: i64,            // 8 (voting weight snapshot)
+    pub voting_ends_at: i64,        // 8
+    pub votes_for: u64,             // 8
+    pub votes_against: u64,         // 8
+    pub status: ProposalStatus,     // 1
+    pub bump: u8,                   // 1
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + 8 + 32 + ParameterChange::MAX_LEN + 8 + 8 + 8 + 8 + 1 + 1;
+}
+
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,           // 32
+    pub voter: Pubkey,              // 32
+    pub support: bool,              // 1
+    pub weight: u64,                // 8
+    pub bump: u8,                   // 1
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 1;
+}
+
+#[account]
+pub struct DeveloperRegistry {
+    pub authority: Pubkey,          // 32
+    pub total_developers: u64,      // 8
+    pub bump: u8,                   // 1
+    // Fields below were appended after the first release; see migrate_account
+    pub pending_authority: Option<Pubkey>, // 1 + 32
+}
+
+impl DeveloperRegistry {
+    pub const LEN: usize = 8 + 32 + 8 + 1 + (1 + 32);
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    SuperAdmin,
+    Curator,
+    Moderator,
+    Attester,
+    Treasurer,
+    Pauser,
+}
+
+impl Role {
+    pub fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RoleMember {
+    pub member: Pubkey,             // 32
+    pub roles: u8,                  // 1 (bitmask of Role::mask)
+}
+
+#[account]
+pub struct Roles {
+    pub members: Vec<RoleMember>,   // 4 + 33 * MAX_ROLE_MEMBERS
+    pub bump: u8,                   // 1
+}
+
+impl Roles {
+    pub const LEN: usize = 8 + (4 + (32 + 1) * MAX_ROLE_MEMBERS) + 1;
+
+    /// Used in account constraints to gate admin instructions by role.
+    pub fn has_role(&self, member: &Pubkey, role: Role) -> bool {
+        self.members
+            .iter()
+            .any(|m| m.member == *member && m.roles & role.mask() != 0)
+    }
+}
+
+#[account]
+pub struct ProjectStakes {
+    pub project_mint: Pubkey,       // 32
+    pub total_stakes: u64,          // 8 (stake events)
+    pub verification_level: VerificationLevel, // 1 (replaces the is_verified flag)
+    pub bump: u8,                   // 1
+    // Fields below were appended after the first release; see migrate_account
+    pub unique_stakers: u64,        // 8 (positions with a non-zero amount)
+    pub total_staked: u64,          // 8
+    pub total_pending_unstake: u64, // 8 (requested unstakes not withdrawn yet)
+    pub reward_per_share: u128,     // 16 (scaled by REWARD_PRECISION)
+    pub undistributed_rewards: u64, // 8 (funded while nothing was staked)
+    pub status: ProjectStatus,      // 1
+    pub flagged_at: i64,            // 8
+    pub flag_cleared_at: i64,       // 8 (0 until a flag is cleared)
+    pub slash_rate_bps: u16,        // 2 (rate applied when the project was slashed)
+    pub total_reward_weight: u64,   // 8 (active stake scaled by lockup multipliers)
+    pub total_challenged: u64,      // 8 (staked against the project)
+    pub unique_challengers: u64,    // 8
+    pub challenger_rewards: u64,    // 8 (slashed stake owed to challengers)
+    pub reward_streams: Vec<RewardStream>, // 4 + RewardStream::LEN * MAX_REWARD_STREAMS
+}
+
+impl ProjectStakes {
+    pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 8 + 8 + 8 + 16 + 8 + 1 + 8 + 8 + 2 + 8 + 8 + 8 + 8
+        + (4 + RewardStream::LEN * MAX_REWARD_STREAMS);
+
+    /// Brings every sponsored stream up to `now` at the current reward weight.
+    /// Call before total_reward_weight changes. Streams don't accrue unless the project is active.
+    pub fn accrue_reward_streams(&mut self, now: i64) {
+        let total_weight = if self.status == ProjectStatus::Active {
+            self.total_reward_weight
+        } else {
+            0
+        };
+        for stream in self.reward_streams.iter_mut() {
+            stream.accrue(total_weight, now);
+        }
+    }
+
+    /// Spreads newly funded rewards over the current reward weight.
+    pub fn distribute_rewards(&mut self, amount: u64) {
+        if self.total_reward_weight == 0 {
+            self.undistributed_rewards = self.undistributed_rewards.checked_add(amount).unwrap();
+            return;
+        }
+
+        let total = amount.checked_add(self.undistributed_rewards).unwrap();
+        self.undistributed_rewards = 0;
+        self.reward_per_share = self
+            .reward_per_share
+            .checked_add((total as u128) * REWARD_PRECISION / (self.total_reward_weight as u128))
+            .unwrap();
+    }
+
+    /// Applies a change in one position's reward weight to the project total.
+    pub fn apply_weight_change(&mut self, before: u64, after: u64) {
+        self.total_reward_weight = self
+            .total_reward_weight
+            .saturating_sub(before)
+            .checked_add(after)
+            .unwrap();
+    }
+
+    /// Applies a change in one position's staked amount to the project totals.
+    pub fn apply_position_change(&mut self, before: u64, after: u64) {
+        if before == 0 && after > 0 {
+            self.unique_stakers = self.unique_stakers.checked_add(1).unwrap();
+        } else if before > 0 && after == 0 {
+            self.unique_stakers = self.unique_stakers.saturating_sub(1);
+        }
+
+        self.total_staked = self
+            .total_staked
+            .saturating_sub(before)
+            .checked_add(after)
+            .unwrap();
+    }
+
+    /// Highest tier whose stake and risk requirements (and those of every lower tier) are met.
+    /// Stake against the project counts against the stake requirement.
+    pub fn compute_verification_level(&self, config: &ProtocolConfig, risk_score: u16) -> VerificationLevel {
+        let net_stake = self.total_staked.saturating_sub(self.total_challenged);
+        let levels = [
+            VerificationLevel::Bronze,
+            VerificationLevel::Silver,
+            VerificationLevel::Gold,
+        ];
+
+        let mut level = VerificationLevel::None;
+        for (tier, candidate) in config.verification_tiers.iter().zip(levels) {
+            if self.unique_stakers < tier.min_unique_stakers
+                || net_stake < tier.min_total_stake
+                || risk_score > tier.max_risk_score
+            {
+                break;
+            }
+            level = candidate;
+        }
+
+        level
+    }
+}
+
+/// Linear emission schedule for one project-sponsored reward mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct RewardStream {
+    pub reward_mint: Pubkey,        // 32
+    pub rate: u64,                  // 8 (reward tokens per second)
+    pub start_time: i64,            // 8
+    pub end_time: i64,              // 8
+    pub last_update: i64,           // 8
+    pub reward_per_share: u128,     // 16 (scaled by REWARD_PRECISION)
+    pub unallocated: u64,           // 8 (emitted while nothing earned; reclaimable by the project)
+}
+
+impl RewardStream {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 16 + 8;
+
+    /// Emissions released while nothing earns are set aside for the project to reclaim.
+    pub fn accrue(&mut self, total_weight: u64, now: i64) {
+        let until = now.min(self.end_time);
+        if until <= self.last_update {
+            return;
+        }
+
+        let emitted = (self.rate as u128)
+            .checked_mul((until - self.last_update) as u128)
+            .unwrap();
+        if total_weight > 0 {
+            self.reward_per_share = self
+                .reward_per_share
+                .checked_add(emitted.checked_mul(REWARD_PRECISION).unwrap() / total_weight as u128)
+                .unwrap();
+        } else {
+            self.unallocated = self.unallocated.checked_add(emitted as u64).unwrap();
+        }
+        self.last_update = until;
+    }
+}
+
+#[account]
+pub struct ProjectRewardVault {
+    pub project_mint: Pubkey,       // 32
+    pub reward_mint: Pubkey,        // 32
+    pub stream_index: u8,           // 1 (position in ProjectStakes::reward_streams)
+    pub total_funded: u64,          // 8
+    pub bump: u8,                   // 1
+}
+
+impl ProjectRewardVault {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SlashDestination {
+    RewardPool,
+    InsuranceFund,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectStatus {
+    Active,
+    Flagged,
+    Slashed,
+}
+
+#[account]
+pub struct SlashEvent {
+    pub project_mint: Pubkey,       // 32
+    pub slashed_by: Pubkey,         // 32
+    pub rate_bps: u16,              // 2
+    pub staked_balance: u64,        // 8 (active and pending stake before the slash)
+    pub slashed_amount: u64,        // 8
+    pub slashed_at: i64,            // 8
+    pub bump: u8,                   // 1
+}
+
+impl SlashEvent {
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 8 + 8 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationLevel {
+    None,
+    Bronze,
+    Silver,
+    Gold,
+}
+
+#[account]
+pub struct RiskAttestation {
+    pub mint: Pubkey,               // 32
+    pub attester: Pubkey,           // 32
+    pub risk_score: u16,            // 2 (0 = safest, MAX_RISK_SCORE = riskiest)
+    pub attested_at: i64,           // 8
+    pub expires_at: i64,            // 8
+    pub bump: u8,                   // 1
+}
+
+impl RiskAttestation {
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct UserStake {
+    pub user: Pubkey,               // 32
+    pub project_mint: Pubkey,       // 32
+    pub amount: u64,                // 8
+    pub staked_at: i64,             // 8
+    pub unstake_requested_at: Option<i64>, // 1 + 8
+    pub bump: u8,                   // 1
+    // Fields below were appended after the first release; see migrate_user_stake
+    pub pending_unstake_amount: u64, // 8
+    pub reward_debt: u128,          // 16 (reward_per_share checkpoint * reward weight)
+    pub pending_rewards: u64,       // 8
+    pub sponsored_rewards: [StreamCheckpoint; MAX_REWARD_STREAMS], // StreamCheckpoint::LEN * MAX_REWARD_STREAMS
+    pub slash_applied: bool,        // 1
+    pub lockup: LockupTier,         // 1
+    pub unlock_at: i64,             // 8
+    pub receipt_balance: u64,       // 8 (receipts minted against this position)
+    pub active_history: [ActiveCheckpoint; ACTIVE_HISTORY_LEN], // ActiveCheckpoint::LEN * ACTIVE_HISTORY_LEN (oldest first)
+    pub unstake_available_at: i64,  // 8 (end of the cooldown for the pending request)
+    pub reward_points: u64,         // 8 (points this position credited to the profile)
+}
+
+impl UserStake {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + (1 + 8) + 1 + 8 + 16 + 8
+        + StreamCheckpoint::LEN * MAX_REWARD_STREAMS + 1 + 1 + 8 + 8
+        + ActiveCheckpoint::LEN * ACTIVE_HISTORY_LEN + 8 + 8;
+
+    /// Records a change of the active amount away from `previous_active` so voting
+    /// snapshots can look past it. Call after the position was updated.
+    pub fn record_active_change(&mut self, previous_active: u64, now: i64) {
+        if self.active_amount() == previous_active {
+            return;
+        }
+
+        let history = &mut self.active_history;
+        // An earlier change at this timestamp already recorded the amount held before it
+        if history[ACTIVE_HISTORY_LEN - 1].changed_at == now {
+            return;
+        }
+        // Once full, merge the adjacent checkpoints with the closest amounts into the
+        // later one at the lower amount: snapshots in that span are understated by as
+        // little as possible and never overstated
+        if history[0].changed_at != 0 {
+            let merged = (0..ACTIVE_HISTORY_LEN - 1)
+                .min_by_key(|&i| history[i].active_before.abs_diff(history[i + 1].active_before))
+                .unwrap();
+            history[merged + 1].active_before =
+                history[merged + 1].active_before.min(history[merged].active_before);
+            history[..=merged].rotate_right(1);
+        }
+        history.rotate_left(1);
+        history[ACTIVE_HISTORY_LEN - 1] = ActiveCheckpoint {
+            changed_at: now,
+            active_before: previous_active,
+        };
+    }
+
+    /// Active amount held at `at`: the amount before the first change made at or after it.
+    pub fn active_amount_at(&self, at: i64) -> u64 {
+        self.active_history
+            .iter()
+            .find(|checkpoint| checkpoint.changed_at != 0 && checkpoint.changed_at >= at)
+            .map_or(self.active_amount(), |checkpoint| checkpoint.active_before)
+    }
+
+    /// Receipts backing `amount` of this position. Proportional, so a slashed
+    /// position still needs all of its receipts back to be fully withdrawn.
+    pub fn receipts_for(&self, amount: u64) -> u64 {
+        if self.amount == 0 {
+            return 0;
+        }
+        (self.receipt_balance as u128 * amount as u128 / self.amount as u128) as u64
+    }
+
+    /// Reward points credited for `amount` of this position, at the multiplier it was staked with.
+    pub fn reward_points_for(&self, amount: u64) -> u64 {
+        if self.amount == 0 {
+            return 0;
+        }
+        (self.reward_points as u128 * amount as u128 / self.amount as u128) as u64
+    }
+
+    /// Scales the position down once after its project was slashed. Settle rewards first.
+    pub fn apply_slash(&mut self, project_stakes: &ProjectStakes) {
+        if project_stakes.status != ProjectStatus::Slashed || self.slash_applied {
+            return;
+        }
+
+        self.amount = apply_slash_rate(self.amount, project_stakes.slash_rate_bps);
+        self.pending_unstake_amount =
+            apply_slash_rate(self.pending_unstake_amount, project_stakes.slash_rate_bps);
+        for checkpoint in self.active_history.iter_mut() {
+            checkpoint.active_before =
+                apply_slash_rate(checkpoint.active_before, project_stakes.slash_rate_bps);
+        }
+        self.slash_applied = true;
+    }
+
+    /// Credits rewards accrued at the current reward weight and moves the checkpoints.
+    /// Call before the reward weight changes.
+    pub fn settle_rewards(&mut self, project_stakes: &ProjectStakes) {
+        let weight = self.reward_weight() as u128;
+
+        let accrued = (weight * project_stakes.reward_per_share / REWARD_PRECISION)
+            .saturating_sub(self.reward_debt);
+        self.pending_rewards = self.pending_rewards.checked_add(accrued as u64).unwrap();
+
+        for (checkpoint, stream) in self
+            .sponsored_rewards
+            .iter_mut()
+            .zip(project_stakes.reward_streams.iter())
+        {
+            let accrued = (weight * stream.reward_per_share / REWARD_PRECISION)
+                .saturating_sub(checkpoint.reward_debt);
+            checkpoint.pending_rewards = checkpoint.pending_rewards.checked_add(accrued as u64).unwrap();
+        }
+
+        self.checkpoint_rewards(project_stakes);
+    }
+
+    /// Re-bases the checkpoints on the reward weight. Call after it changes.
+    pub fn checkpoint_rewards(&mut self, project_stakes: &ProjectStakes) {
+        let weight = self.reward_weight() as u128;
+
+        self.reward_debt = weight * project_stakes.reward_per_share / REWARD_PRECISION;
+        for (checkpoint, stream) in self
+            .sponsored_rewards
+            .iter_mut()
+            .zip(project_stakes.reward_streams.iter())
+        {
+            checkpoint.reward_debt = weight * stream.reward_per_share / REWARD_PRECISION;
+        }
+    }
+
+    pub fn has_unclaimed_rewards(&self) -> bool {
+        self.pending_rewards > 0 || self.sponsored_rewards.iter().any(|c| c.pending_rewards > 0)
+    }
+
+    /// Staked amount not queued for withdrawal; only this counts toward verification.
+    pub fn active_amount(&self) -> u64 {
+        self.amount.saturating_sub(self.pending_unstake_amount)
+    }
+
+    /// Share of the project's staking rewards: the active amount boosted by the lockup.
+    pub fn reward_weight(&self) -> u64 {
+        self.lockup.apply_multiplier(self.active_amount())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StakeBatchEntry {
+    pub project_mint: Pubkey,
+    pub amount: u64,
+    pub lockup: LockupTier,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockupTier {
+    None,
+    Days30,
+    Days90,
+    Days180,
+}
+
+impl LockupTier {
+    pub fn duration(self) -> i64 {
+        match self {
+            LockupTier::None => 0,
+            LockupTier::Days30 => 2592000,
+            LockupTier::Days90 => 7776000,
+            LockupTier::Days180 => 15552000,
+        }
+    }
+
+    pub fn multiplier_bps(self) -> u64 {
+        match self {
+            LockupTier::None => 10000,
+            LockupTier::Days30 => 12500,
+            LockupTier::Days90 => 15000,
+            LockupTier::Days180 => 20000,
+        }
+    }
+
+    pub fn apply_multiplier(self, amount: u64) -> u64 {
+        (amount as u128 * self.multiplier_bps() as u128 / 10000) as u64
+    }
+}
+
+/// Per-stream counterpart of UserStake::reward_debt / pending_rewards.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct StreamCheckpoint {
+    pub reward_debt: u128,          // 16
+    pub pending_rewards: u64,       // 8
+}
+
+impl StreamCheckpoint {
+    pub const LEN: usize = 16 + 8;
+}
+
+/// Active amount a position held before `changed_at`. Unused slots are zeroed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ActiveCheckpoint {
+    pub changed_at: i64,            // 8
+    pub active_before: u64,         // 8
+}
+
+impl ActiveCheckpoint {
+    pub const LEN: usize = 8 + 8;
+}
+
+#[account]
+pub struct ChallengeStake {
+    pub challenger: Pubkey,         // 32
+    pub project_mint: Pubkey,       // 32
+    pub amount: u64,                // 8
+    pub staked_at: i64,             // 8 (dispute window runs from here)
+    pub bump: u8,                   // 1
+}
+
+impl ChallengeStake {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReportStatus {
+    Pending,
+    Upheld,
+    Rejected,
+}
+
+#[account]
+pub struct ScamReport {
+    pub reporter: Pubkey,           // 32
+    pub mint: Pubkey,               // 32
+    pub evidence_hash: String,      // 4 + 100
+    pub bond_amount: u64,           // 8
+    pub status: ReportStatus,       // 1
+    pub created_at: i64,            // 8
+    pub resolved_at: Option<i64>,   // 1 + 8
+    pub resolver: Option<Pubkey>,   // 1 + 32
+    pub bump: u8,                   // 1
+    pub vault_bump: u8,             // 1
+}
+
+impl ScamReport {
+    pub const LEN: usize = 8 + 32 + 32 + (4 + 100) + 8 + 1 + 8 + (1 + 8) + (1 + 32) + 1 + 1;
+}
+
+// ============================================
+// LEGACY LAYOUTS
+// ============================================
+// Layouts written by the first release. Only the migration instructions read them.
+
+#[derive(AnchorDeserialize)]
+pub struct LegacyTokenEntry {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub ipfs_hash: String,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+impl LegacyTokenEntry {
+    pub const LEN: usize = 8 + 32 + 32 + (4 + 50) + (4 + 10) + (4 + 100) + 8 + 1;
+}
+
+#[derive(AnchorDeserialize)]
+pub struct LegacyRewardPool {
+    pub authority: Pubkey,
+    pub total_deposited: u64,
+    pub total_distributed: u64,
+    pub last_distribution: i64,
+    pub distribution_interval: i64,
+    pub developer_share_bps: u16,
+    pub user_share_bps: u16,
+    pub bump: u8,
+}
+
+impl LegacyRewardPool {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 2 + 2 + 1;
+}
+
+#[derive(AnchorDeserialize)]
+pub struct LegacyDeveloperRegistry {
+    pub authority: Pubkey,
+    pub total_developers: u64,
+    pub bump: u8,
+}
+
+impl LegacyDeveloperRegistry {
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+}
+
+#[derive(AnchorDeserialize)]
+pub struct LegacyProjectStakes {
+    pub project_mint: Pubkey,
+    pub total_stakes: u64,
+    pub is_verified: bool,
+    pub bump: u8,
+}
+
+impl LegacyProjectStakes {
+    pub const LEN: usize = 8 + 32 + 8 + 1 + 1;
+}
+
+#[derive(AnchorDeserialize)]
+pub struct LegacyUserStake {
+    pub user: Pubkey,
+    pub project_mint: Pubkey,
+    pub amount: u64,
+    pub staked_at: i64,
+    pub unstake_requested_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl LegacyUserStake {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + (1 + 8) + 1;
+}
+
+// ============================================
+// CONTEXTS
+// ============================================
+
+#[derive(Accounts)]
+#[instruction(name: String, symbol: String)]
+pub struct RegisterToken<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Registry) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Token mint being registered
+    pub mint: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = TokenEntry::LEN,
         seeds = [b"token_entry", mint.key().as_ref()],
         bump
     )]
     pub token_entry: Account<'info, TokenEntry>,
 
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SymbolIndex::LEN,
+        seeds = [b"symbol_index".as_ref(), &symbol_seed(&symbol)],
+        bump
+    )]
+    pub symbol_index: Account<'info, SymbolIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SymbolIndexPage::space(0),
+        seeds = [
+            b"symbol_index_page".as_ref(),
+            &symbol_seed(&symbol),
+            &symbol_index.open_page.to_le_bytes()
+        ],
+        bump
+    )]
+    pub symbol_index_page: Account<'info, SymbolIndexPage>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AuthorityIndex::LEN,
+        seeds = [b"authority_index", authority.key().as_ref()],
+        bump
+    )]
+    pub authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AuthorityIndexPage::space(0),
+        seeds = [
+            b"authority_index_page",
+            authority.key().as_ref(),
+            &authority_index.open_page.to_le_bytes()
+        ],
+        bump
+    )]
+    pub authority_index_page: Account<'info, AuthorityIndexPage>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTokenEntry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Registry) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
+        bump = token_entry.bump,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+}
+
+#[derive(Accounts)]
+pub struct TransferTokenAuthority<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Registry) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Any wallet can receive authority over a token entry
+    pub new_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
+        bump = token_entry.bump,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+
+    /// Required unless the entry predates the authority index
+    #[account(
+        mut,
+        seeds = [
+            b"authority_index_page",
+            authority.key().as_ref(),
+            &token_entry.authority_index_page.unwrap_or_default().to_le_bytes()
+        ],
+        bump = current_authority_index_page.bump
+    )]
+    pub current_authority_index_page: Option<Account<'info, AuthorityIndexPage>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AuthorityIndex::LEN,
+        seeds = [b"authority_index", new_authority.key().as_ref()],
+        bump
+    )]
+    pub new_authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AuthorityIndexPage::space(0),
+        seeds = [
+            b"authority_index_page",
+            new_authority.key().as_ref(),
+            &new_authority_index.open_page.to_le_bytes()
+        ],
+        bump
+    )]
+    pub new_authority_index_page: Account<'info, AuthorityIndexPage>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterToken<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Registry) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        close = authority,
+        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
+        bump = token_entry.bump,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+
+    /// Required unless the entry predates the authority index
+    #[account(
+        mut,
+        seeds = [
+            b"authority_index_page",
+            authority.key().as_ref(),
+            &token_entry.authority_index_page.unwrap_or_default().to_le_bytes()
+        ],
+        bump = authority_index_page.bump
+    )]
+    pub authority_index_page: Option<Account<'info, AuthorityIndexPage>>,
+
+    #[account(
+        mut,
+        seeds = [b"symbol_index".as_ref(), &symbol_seed(&token_entry.symbol)],
+        bump = symbol_index.bump
+    )]
+    pub symbol_index: Account<'info, SymbolIndex>,
+
+    /// Required unless the entry predates the symbol index
+    #[account(
+        mut,
+        seeds = [
+            b"symbol_index_page".as_ref(),
+            &symbol_seed(&token_entry.symbol),
+            &token_entry.symbol_index_page.unwrap_or_default().to_le_bytes()
+        ],
+        bump = symbol_index_page.bump
+    )]
+    pub symbol_index_page: Option<Account<'info, SymbolIndexPage>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVerifiedSymbol<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Registry) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
+        bump = token_entry.bump,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+
+    #[account(
+        seeds = [b"project_stakes", token_entry.mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SymbolIndex::LEN,
+        seeds = [b"symbol_index".as_ref(), &symbol_seed(&token_entry.symbol)],
+        bump
+    )]
+    pub symbol_index: Account<'info, SymbolIndex>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseVerifiedSymbol<'info> {
+    #[account(
+        mut,
+        seeds = [b"symbol_index".as_ref(), &symbol_seed(&token_entry.symbol)],
+        bump = symbol_index.bump,
+        constraint = symbol_index.verified_mint == Some(token_entry.mint) @ ChainProofError::SymbolNotClaimed
+    )]
+    pub symbol_index: Account<'info, SymbolIndex>,
+
+    #[account(
+        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
+        bump = token_entry.bump,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+
+    #[account(
+        seeds = [b"project_stakes", token_entry.mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+}
+
+#[derive(Accounts)]
+#[instruction(symbol: String)]
+pub struct ChangeTokenSymbol<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Registry) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
+        bump = token_entry.bump,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SymbolIndex::LEN,
+        seeds = [b"symbol_index".as_ref(), &symbol_seed(&token_entry.symbol)],
+        bump
+    )]
+    pub current_symbol_index: Account<'info, SymbolIndex>,
+
+    /// Required unless the entry predates the symbol index
+    #[account(
+        mut,
+        seeds = [
+            b"symbol_index_page".as_ref(),
+            &symbol_seed(&token_entry.symbol),
+            &token_entry.symbol_index_page.unwrap_or_default().to_le_bytes()
+        ],
+        bump = current_symbol_index_page.bump
+    )]
+    pub current_symbol_index_page: Option<Account<'info, SymbolIndexPage>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SymbolIndex::LEN,
+        seeds = [b"symbol_index".as_ref(), &symbol_seed(&symbol)],
+        bump
+    )]
+    pub new_symbol_index: Account<'info, SymbolIndex>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SymbolIndexPage::space(0),
+        seeds = [
+            b"symbol_index_page".as_ref(),
+            &symbol_seed(&symbol),
+            &new_symbol_index.open_page.to_le_bytes()
+        ],
+        bump
+    )]
+    pub new_symbol_index_page: Account<'info, SymbolIndexPage>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReviewListing<'info> {
+    pub curator: Signer<'info>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&curator.key(), Role::Curator) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        mut,
+        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
+        bump = token_entry.bump,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRoles<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump,
+        has_one = authority
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Roles::LEN,
+        seeds = [b"roles"],
+        bump
+    )]
+    pub roles: Account<'info, Roles>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRoles<'info> {
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&super_admin.key(), Role::SuperAdmin) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolConfig<'info> {
+    #[account(mut)]
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&super_admin.key(), Role::SuperAdmin) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        init,
+        payer = super_admin,
+        space = ProtocolConfig::LEN,
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    pub pauser: Signer<'info>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&pauser.key(), Role::Pauser) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct QueueAdminAction<'info> {
+    #[account(mut)]
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&super_admin.key(), Role::SuperAdmin) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = super_admin,
+        space = PendingAction::LEN,
+        seeds = [b"pending_action".as_ref(), &protocol_config.next_action_id.to_le_bytes()],
+        bump
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAdminAction<'info> {
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        seeds = [b"developer_registry"],
+        bump = developer_registry.bump
+    )]
+    pub developer_registry: Account<'info, DeveloperRegistry>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_action".as_ref(), &pending_action.action_id.to_le_bytes()],
+        bump = pending_action.bump
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+
+    /// CHECK: Receives the pending action rent, checked against the action
+    #[account(mut, address = pending_action.proposer)]
+    pub proposer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAdminAction<'info> {
+    pub canceller: Signer<'info>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_action".as_ref(), &pending_action.action_id.to_le_bytes()],
+        bump = pending_action.bump
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+
+    /// CHECK: Receives the pending action rent, checked against the action
+    #[account(mut, address = pending_action.proposer)]
+    pub proposer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::LEN,
+        seeds = [b"proposal".as_ref(), &protocol_config.next_proposal_id.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal".as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = VoteRecord::LEN,
+        seeds = [b"vote_record", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal".as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = PendingAction::LEN,
+        seeds = [b"pending_action".as_ref(), &protocol_config.next_action_id.to_le_bytes()],
+        bump
+    )]
+    pub pending_action: Option<Account<'info, PendingAction>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardPool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = RewardPool::LEN,
+        seeds = [b"reward_pool"],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToPool<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::PoolDeposits) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(mut)]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(mut)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Distribution) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&authority.key(), Role::Treasurer) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    pub developer_registry: Account<'info, DeveloperRegistry>,
+
+    #[account(mut)]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    #[account(mut)]
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&super_admin.key(), Role::SuperAdmin) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(address = STAKE_TOKEN_MINT)]
+    pub stake_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = super_admin,
+        seeds = [b"insurance_fund"],
+        bump,
+        token::mint = stake_mint,
+        token::authority = protocol_config
+    )]
+    pub insurance_fund: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DisburseInsuranceFund<'info> {
+    pub treasurer: Signer<'info>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&treasurer.key(), Role::Treasurer) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Distribution) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"insurance_fund"],
+        bump
+    )]
+    pub insurance_fund: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRewardPoolAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptRewardPoolAuthority<'info> {
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump,
+        constraint = reward_pool.pending_authority == Some(new_authority.key()) @ ChainProofError::Unauthorized
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+}
+
+#[derive(Accounts)]
+#[instruction(username: String, referral_code: Option<String>)]
+pub struct CreateProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Profiles) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserProfile::LEN,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Profiles) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = wallet
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    /// CHECK: Checked via has_one constraint
+    pub wallet: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDeveloperRegistry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = DeveloperRegistry::LEN,
+        seeds = [b"developer_registry"],
+        bump
+    )]
+    pub developer_registry: Account<'info, DeveloperRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageDeveloperRegistryAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"developer_registry"],
+        bump = developer_registry.bump
+    )]
+    pub developer_registry: Account<'info, DeveloperRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptDeveloperRegistryAuthority<'info> {
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"developer_registry"],
+        bump = developer_registry.bump,
+        constraint = developer_registry.pending_authority == Some(new_authority.key()) @ ChainProofError::Unauthorized
+    )]
+    pub developer_registry: Account<'info, DeveloperRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterDeveloper<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Profiles) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(mut)]
+    pub developer_registry: Account<'info, DeveloperRegistry>,
+
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProjectStakes<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Token mint being staked on
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ProjectStakes::LEN,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReceiptMint<'info> {
+    #[account(mut)]
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&super_admin.key(), Role::SuperAdmin) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(address = STAKE_TOKEN_MINT)]
+    pub stake_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = super_admin,
+        seeds = [b"receipt_mint"],
+        bump,
+        mint::decimals = stake_mint.decimals,
+        mint::authority = receipt_mint_authority
+    )]
+    pub receipt_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA that signs receipt mints
+    #[account(
+        seeds = [b"receipt_mint_authority"],
+        bump
+    )]
+    pub receipt_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeOnProject<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Token mint being staked on
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStake::LEN,
+        seeds = [b"user_stake", user.key().as_ref(), project_mint.key().as_ref()],
+        bump
+    )]
+    pub user_stake: Box<Account<'info, UserStake>>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Box<Account<'info, ProjectStakes>>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = stake_vault.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = stake_vault.owner == project_stakes.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt_mint"],
+        bump
+    )]
+    pub receipt_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA that signs receipt mints
+    #[account(
+        seeds = [b"receipt_mint_authority"],
+        bump
+    )]
+    pub receipt_mint_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::mint = receipt_mint,
+        token::authority = user
+    )]
+    pub user_receipt_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeBatch<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt_mint"],
+        bump
+    )]
+    pub receipt_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA that signs receipt mints
+    #[account(
+        seeds = [b"receipt_mint_authority"],
+        bump
+    )]
+    pub receipt_mint_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::mint = receipt_mint,
+        token::authority = user
+    )]
+    pub user_receipt_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_stake", user.key().as_ref(), user_stake.project_mint.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = user
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", user_stake.project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", user_stake.project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelUnstake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_stake", user.key().as_ref(), user_stake.project_mint.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = user
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", user_stake.project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", user_stake.project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteUnstake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Project mint
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_stake", user.key().as_ref(), project_mint.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = user
+    )]
+    pub user_stake: Box<Account<'info, UserStake>>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Box<Account<'info, ProjectStakes>>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = stake_vault.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = stake_vault.owner == project_stakes.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt_mint"],
+        bump
+    )]
+    pub receipt_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = receipt_mint,
+        token::authority = user
+    )]
+    pub user_receipt_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CompleteUnstake<'info> {
+    /// Pays out the pending unstake, sending `penalty_bps` of it to `penalty_account`,
+    /// and closes the position once it is empty. Returns the penalty taken. The caller
+    /// checks that an unstake was requested and, unless it charges a penalty, the cooldown.
+    fn withdraw_pending(&mut self, penalty_bps: u16, penalty_account: Option<AccountInfo<'info>>) -> Result<u64> {
+        let clock = Clock::get()?;
+        require!(
+            self.project_stakes.status != ProjectStatus::Flagged,
+            ChainProofError::ProjectFlagged
+        );
+
+        let Withdrawal {
+            amount,
+            remaining,
+            penalty,
+            receipts_burned,
+        } = settle_withdrawal(
+            &mut self.user_stake,
+            &mut self.project_stakes,
+            &mut self.user_profile,
+            penalty_bps,
+        )?;
+
+        // Receipts minted against the withdrawn portion have to be returned
+        if receipts_burned > 0 {
+            burn_receipts(
+                &self.receipt_mint.to_account_info(),
+                &self.user_receipt_account.to_account_info(),
+                &self.user.to_account_info(),
+                &self.token_program.to_account_info(),
+                receipts_burned,
+            )?;
+        }
+
+        // Transfer stake tokens back to user (the vault is owned by the project_stakes PDA)
+        let project_mint_key = self.project_mint.key();
+        let seeds = &[
+            b"project_stakes",
+            project_mint_key.as_ref(),
+            &[self.project_stakes.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: self.stake_vault.to_account_info(),
+            to: self.user_token_account.to_account_info(),
+            authority: self.project_stakes.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount - penalty)?;
+
+        if penalty > 0 {
+            let cpi_accounts = Transfer {
+                from: self.stake_vault.to_account_info(),
+                to: penalty_account.ok_or(ChainProofError::InvalidTokenAccount)?,
+                authority: self.project_stakes.to_account_info(),
+            };
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, penalty)?;
+        }
+
+        // Check if project loses (or regains) a verification level
+        update_verification_level(
+            &mut self.project_stakes,
+            &self.protocol_config,
+            read_risk_attestation(&self.risk_attestation)?.as_ref(),
+            clock.unix_timestamp,
+        );
+
+        let user_stake = &self.user_stake;
+        emit!(Unstaked {
+            user: user_stake.user,
+            project_mint: user_stake.project_mint,
+            amount,
+            remaining,
+            penalty,
+            receipts_burned,
+        });
+
+        // Fully withdrawn positions are closed so the user gets the rent back,
+        // unless they still have staking rewards to claim
+        if remaining == 0 && !user_stake.has_unclaimed_rewards() {
+            emit!(UserStakeClosed {
+                user: user_stake.user,
+                project_mint: user_stake.project_mint,
+            });
+            self.user_stake.close(self.user.to_account_info())?;
+        }
+
+        Ok(penalty)
+    }
+}
+
+#[derive(Accounts)]
+pub struct EmergencyUnstake<'info> {
+    pub unstake: CompleteUnstake<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = pool_token_account.owner == reward_pool.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct RestakeToProject<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Project mint the stake leaves
+    pub from_project_mint: AccountInfo<'info>,
+
+    /// CHECK: Project mint the stake moves to
+    #[account(constraint = to_project_mint.key() != from_project_mint.key() @ ChainProofError::SameProject)]
+    pub to_project_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_stake", user.key().as_ref(), from_project_mint.key().as_ref()],
+        bump = from_user_stake.bump,
+        has_one = user
+    )]
+    pub from_user_stake: Box<Account<'info, UserStake>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStake::LEN,
+        seeds = [b"user_stake", user.key().as_ref(), to_project_mint.key().as_ref()],
+        bump
+    )]
+    pub to_user_stake: Box<Account<'info, UserStake>>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", from_project_mint.key().as_ref()],
+        bump = from_project_stakes.bump
+    )]
+    pub from_project_stakes: Box<Account<'info, ProjectStakes>>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", to_project_mint.key().as_ref()],
+        bump = to_project_stakes.bump
+    )]
+    pub to_project_stakes: Box<Account<'info, ProjectStakes>>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", from_project_mint.key().as_ref()],
+        bump
+    )]
+    pub from_risk_attestation: UncheckedAccount<'info>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", to_project_mint.key().as_ref()],
+        bump
+    )]
+    pub to_risk_attestation: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        mut,
+        constraint = from_stake_vault.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = from_stake_vault.owner == from_project_stakes.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub from_stake_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = to_stake_vault.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = to_stake_vault.owner == to_project_stakes.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub to_stake_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemReceipts<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Project mint both positions are on
+    pub project_mint: AccountInfo<'info>,
+
+    /// CHECK: Staker whose position the receipts are redeemed against
+    #[account(constraint = owner.key() != holder.key() @ ChainProofError::Unauthorized)]
+    pub owner: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_stake", owner.key().as_ref(), project_mint.key().as_ref()],
+        bump = owner_stake.bump
+    )]
+    pub owner_stake: Box<Account<'info, UserStake>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", owner.key().as_ref()],
+        bump = owner_profile.bump
+    )]
+    pub owner_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = UserStake::LEN,
+        seeds = [b"user_stake", holder.key().as_ref(), project_mint.key().as_ref()],
+        bump
+    )]
+    pub holder_stake: Box<Account<'info, UserStake>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", holder.key().as_ref()],
+        bump = holder_profile.bump
+    )]
+    pub holder_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Box<Account<'info, ProjectStakes>>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt_mint"],
+        bump
+    )]
+    pub receipt_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = receipt_mint,
+        token::authority = holder
+    )]
+    pub holder_receipt_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundProjectRewards<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Project mint whose stakers are rewarded
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    #[account(address = STAKE_TOKEN_MINT)]
+    pub stake_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = funder
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = funder,
+        seeds = [b"stake_rewards", project_mint.key().as_ref()],
+        bump,
+        token::mint = stake_mint,
+        token::authority = project_stakes
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStakeRewards<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Distribution) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_stake", user.key().as_ref(), user_stake.project_mint.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = user
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", user_stake.project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_rewards", user_stake.project_mint.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProjectRewardVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Project mint whose stakers are rewarded
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"token_entry", project_mint.key().as_ref()],
+        bump = token_entry.bump,
+        has_one = authority,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ProjectRewardVault::LEN,
+        seeds = [b"project_reward_vault", project_mint.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: Account<'info, ProjectRewardVault>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"sponsored_rewards", reward_vault.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = reward_vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundProjectRewardVault<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"token_entry", reward_vault.project_mint.as_ref()],
+        bump = token_entry.bump,
+        has_one = authority,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", reward_vault.project_mint.as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"project_reward_vault",
+            reward_vault.project_mint.as_ref(),
+            reward_vault.reward_mint.as_ref()
+        ],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Account<'info, ProjectRewardVault>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsored_rewards", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_vault.reward_mint,
+        token::authority = authority
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimSponsoredRewards<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Distribution) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_stake", user.key().as_ref(), user_stake.project_mint.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = user
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", user_stake.project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    #[account(
+        seeds = [
+            b"project_reward_vault",
+            user_stake.project_mint.key().as_ref(),
+            reward_vault.reward_mint.as_ref()
+        ],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Account<'info, ProjectRewardVault>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsored_rewards", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == reward_vault.reward_mint @ ChainProofError::InvalidTokenAccount
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimUnallocatedRewards<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"token_entry", reward_vault.project_mint.as_ref()],
+        bump = token_entry.bump,
+        has_one = authority,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", reward_vault.project_mint.as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    #[account(
+        seeds = [
+            b"project_reward_vault",
+            reward_vault.project_mint.as_ref(),
+            reward_vault.reward_mint.as_ref()
+        ],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Account<'info, ProjectRewardVault>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsored_rewards", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_vault.reward_mint,
+        token::authority = authority
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ModerateProject<'info> {
+    pub moderator: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&moderator.key(), Role::Moderator) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    /// CHECK: Project mint under review
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+}
+
+#[derive(Accounts)]
+pub struct ClearProjectFlag<'info> {
+    pub moderator: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&moderator.key(), Role::Moderator) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    /// CHECK: Project mint under review
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashProject<'info> {
+    #[account(mut)]
+    pub moderator: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&moderator.key(), Role::Moderator) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
+
+    /// CHECK: Project mint being slashed
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    #[account(
+        mut,
+        constraint = stake_vault.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = stake_vault.owner == project_stakes.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = pool_token_account.owner == reward_pool.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"challenge_vault", project_mint.key().as_ref()],
+        bump
+    )]
+    pub challenge_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"insurance_fund"],
+        bump
+    )]
+    pub insurance_fund: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = moderator,
+        space = SlashEvent::LEN,
+        seeds = [b"slash_event", project_mint.key().as_ref()],
+        bump
+    )]
+    pub slash_event: Account<'info, SlashEvent>,
+
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateTokenEntry<'info> {
+pub struct StakeAgainstProject<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub challenger: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Staking) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Token mint being challenged
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = challenger,
+        space = ChallengeStake::LEN,
+        seeds = [b"challenge_stake", challenger.key().as_ref(), project_mint.key().as_ref()],
+        bump
+    )]
+    pub challenge_stake: Account<'info, ChallengeStake>,
+
+    #[account(address = STAKE_TOKEN_MINT)]
+    pub stake_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = challenger
+    )]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = challenger,
+        seeds = [b"challenge_vault", project_mint.key().as_ref()],
+        bump,
+        token::mint = stake_mint,
+        token::authority = project_stakes
+    )]
+    pub challenge_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawChallenge<'info> {
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Token mint that was challenged
+    pub project_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
+
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = challenger,
+        seeds = [b"challenge_stake", challenger.key().as_ref(), project_mint.key().as_ref()],
+        bump = challenge_stake.bump,
+        has_one = challenger
+    )]
+    pub challenge_stake: Account<'info, ChallengeStake>,
+
+    #[account(address = STAKE_TOKEN_MINT)]
+    pub stake_mint: Account<'info, Mint>,
 
     #[account(
         mut,
-        has_one = authority,
-        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
-        bump = token_entry.bump
+        token::mint = stake_mint,
+        token::authority = challenger
     )]
-    pub token_entry: Account<'info, TokenEntry>,
-}
+    pub challenger_token_account: Account<'info, TokenAccount>,
 
-#[derive(Accounts)]
-pub struct InitializeRewardPool<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"challenge_vault", project_mint.key().as_ref()],
+        bump
+    )]
+    pub challenge_vault: Account<'info, TokenAccount>,
 
+    // Receives the penalty of a failed challenge for the project's backers
     #[account(
-        init,
-        payer = authority,
-        space = RewardPool::LEN,
+        init_if_needed,
+        payer = challenger,
+        seeds = [b"stake_rewards", project_mint.key().as_ref()],
+        bump,
+        token::mint = stake_mint,
+        token::authority = project_stakes
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
         seeds = [b"reward_pool"],
-        bump
+        bump = reward_pool.bump
     )]
     pub reward_pool: Account<'info, RewardPool>,
 
+    #[account(
+        mut,
+        constraint = pool_token_account.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = pool_token_account.owner == reward_pool.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct DepositToPool<'info> {
+pub struct SubmitRiskAttestation<'info> {
     #[account(mut)]
-    pub depositor: Signer<'info>,
+    pub attester: Signer<'info>,
 
-    #[account(mut)]
-    pub reward_pool: Account<'info, RewardPool>,
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&attester.key(), Role::Attester) @ ChainProofError::MissingRole
+    )]
+    pub roles: Account<'info, Roles>,
 
-    #[account(mut)]
-    pub depositor_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Project mint being attested
+    pub project_mint: AccountInfo<'info>,
 
-    #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = attester,
+        space = RiskAttestation::LEN,
+        seeds = [b"risk_attestation", project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: Account<'info, RiskAttestation>,
 
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct DistributeRewards<'info> {
-    #[account(mut, has_one = authority)]
-    pub reward_pool: Account<'info, RewardPool>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
+pub struct RefreshVerification<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 
-    pub developer_registry: Account<'info, DeveloperRegistry>,
+    /// CHECK: Project mint being re-evaluated
+    pub project_mint: AccountInfo<'info>,
 
-    #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Risk attestation PDA for the project; empty when it was never attested
+    #[account(
+        seeds = [b"risk_attestation", project_mint.key().as_ref()],
+        bump
+    )]
+    pub risk_attestation: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(username: String, referral_code: Option<String>)]
-pub struct CreateProfile<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-
+pub struct ExpireLockup<'info> {
     #[account(
-        init,
-        payer = user,
-        space = UserProfile::LEN,
-        seeds = [b"user_profile", user.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"user_stake", user_stake.user.as_ref(), user_stake.project_mint.as_ref()],
+        bump = user_stake.bump
     )]
-    pub user_profile: Account<'info, UserProfile>,
+    pub user_stake: Account<'info, UserStake>,
 
-    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"project_stakes", user_stake.project_mint.as_ref()],
+        bump = project_stakes.bump
+    )]
+    pub project_stakes: Account<'info, ProjectStakes>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateProfile<'info> {
+pub struct CloseUserStake<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"user_profile", user.key().as_ref()],
-        bump = user_profile.bump,
-        has_one = wallet
+        close = user,
+        seeds = [b"user_stake", user.key().as_ref(), user_stake.project_mint.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = user,
+        constraint = user_stake.amount == 0 && !user_stake.has_unclaimed_rewards() @ ChainProofError::StakeNotEmpty
     )]
-    pub user_profile: Account<'info, UserProfile>,
-
-    /// CHECK: Checked via has_one constraint
-    pub wallet: AccountInfo<'info>,
+    pub user_stake: Account<'info, UserStake>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeDeveloperRegistry<'info> {
+pub struct FileScamReport<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub reporter: Signer<'info>,
 
     #[account(
-        init,
-        payer = authority,
-        space = DeveloperRegistry::LEN,
-        seeds = [b"developer_registry"],
-        bump
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Registry) @ ChainProofError::ProtocolPaused
     )]
-    pub developer_registry: Account<'info, DeveloperRegistry>,
+    pub protocol_config: Account<'info, ProtocolConfig>,
 
-    pub system_program: Program<'info, System>,
-}
+    #[account(
+        seeds = [b"user_profile", reporter.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
 
-#[derive(Accounts)]
-pub struct RegisterDeveloper<'info> {
-    #[account(mut)]
-    pub developer_registry: Account<'info, DeveloperRegistry>,
+    #[account(
+        seeds = [b"token_entry", token_entry.mint.key().as_ref()],
+        bump = token_entry.bump,
+        constraint = token_entry.to_account_info().data_len() >= TokenEntry::LEN @ ChainProofError::AccountNotMigrated
+    )]
+    pub token_entry: Account<'info, TokenEntry>,
 
-    pub user_profile: Account<'info, UserProfile>,
-}
+    #[account(
+        init,
+        payer = reporter,
+        space = ScamReport::LEN,
+        seeds = [b"scam_report", token_entry.mint.key().as_ref(), reporter.key().as_ref()],
+        bump
+    )]
+    pub scam_report: Account<'info, ScamReport>,
 
-#[derive(Accounts)]
-pub struct InitializeProjectStakes<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    #[account(address = STAKE_TOKEN_MINT)]
+    pub stake_mint: Account<'info, Mint>,
 
-    /// CHECK: Token mint being staked on
-    pub project_mint: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = reporter
+    )]
+    pub reporter_token_account: Account<'info, TokenAccount>,
 
     #[account(
         init,
-        payer = payer,
-        space = ProjectStakes::LEN,
-        seeds = [b"project_stakes", project_mint.key().as_ref()],
-        bump
+        payer = reporter,
+        seeds = [b"report_vault", scam_report.key().as_ref()],
+        bump,
+        token::mint = stake_mint,
+        token::authority = scam_report
     )]
-    pub project_stakes: Account<'info, ProjectStakes>,
+    pub report_vault: Account<'info, TokenAccount>,
 
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct StakeOnProject<'info> {
+pub struct ResolveScamReport<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub moderator: Signer<'info>,
 
-    /// CHECK: Token mint being staked on
-    pub project_mint: AccountInfo<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused(Subsystem::Distribution) @ ChainProofError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 
     #[account(
-        init_if_needed,
-        payer = user,
-        space = UserStake::LEN,
-        seeds = [b"user_stake", user.key().as_ref(), project_mint.key().as_ref()],
-        bump
+        seeds = [b"roles"],
+        bump = roles.bump,
+        constraint = roles.has_role(&moderator.key(), Role::Moderator) @ ChainProofError::MissingRole
     )]
-    pub user_stake: Account<'info, UserStake>,
+    pub roles: Account<'info, Roles>,
 
     #[account(
         mut,
-        seeds = [b"project_stakes", project_mint.key().as_ref()],
-        bump = project_stakes.bump
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump
     )]
-    pub project_stakes: Account<'info, ProjectStakes>,
+    pub reward_pool: Account<'info, RewardPool>,
 
     #[account(
         mut,
-        seeds = [b"user_profile", user.key().as_ref()],
-        bump = user_profile.bump
+        close = reporter,
+        seeds = [b"scam_report", scam_report.mint.as_ref(), scam_report.reporter.as_ref()],
+        bump = scam_report.bump
     )]
-    pub user_profile: Account<'info, UserProfile>,
+    pub scam_report: Account<'info, ScamReport>,
 
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", scam_report.reporter.as_ref()],
+        bump = reporter_profile.bump
+    )]
+    pub reporter_profile: Account<'info, UserProfile>,
 
-    #[account(mut)]
-    pub stake_vault: Account<'info, TokenAccount>,
+    /// CHECK: Receives the report and vault rent, checked against the report
+    #[account(mut, address = scam_report.reporter)]
+    pub reporter: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"report_vault", scam_report.key().as_ref()],
+        bump = scam_report.vault_bump
+    )]
+    pub report_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = reporter_token_account.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = reporter_token_account.owner == scam_report.reporter @ ChainProofError::InvalidTokenAccount
+    )]
+    pub reporter_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.mint == STAKE_TOKEN_MINT @ ChainProofError::InvalidTokenAccount,
+        constraint = pool_token_account.owner == reward_pool.key() @ ChainProofError::InvalidTokenAccount
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RequestUnstake<'info> {
+pub struct MigrateAccount<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub payer: Signer<'info>,
 
-    #[account(
-        mut,
-        seeds = [b"user_stake", user.key().as_ref(), user_stake.project_mint.key().as_ref()],
-        bump = user_stake.bump,
-        has_one = user
-    )]
-    pub user_stake: Account<'info, UserStake>,
+    /// CHECK: Any account owned by this program; the discriminator picks the layout
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CompleteUnstake<'info> {
+pub struct MigrateUserStake<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub payer: Signer<'info>,
 
-    /// CHECK: Project mint
-    pub project_mint: AccountInfo<'info>,
+    /// CHECK: Legacy UserStake, checked by discriminator and size in the handler
+    #[account(mut, owner = crate::ID)]
+    pub user_stake: UncheckedAccount<'info>,
 
     #[account(
-        mut,
-        seeds = [b"user_stake", user.key().as_ref(), project_mint.key().as_ref()],
-        bump = user_stake.bump,
-        has_one = user
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
     )]
-    pub user_stake: Account<'info, UserStake>,
+    pub protocol_config: Account<'info, ProtocolConfig>,
 
     #[account(
         mut,
-        seeds = [b"project_stakes", project_mint.key().as_ref()],
+        seeds = [b"project_stakes", project_stakes.project_mint.as_ref()],
         bump = project_stakes.bump
     )]
     pub project_stakes: Account<'info, ProjectStakes>,
 
-    #[account(
-        mut,
-        seeds = [b"user_profile", user.key().as_ref()],
-        bump = user_profile.bump
-    )]
-    pub user_profile: Account<'info, UserProfile>,
+    pub system_program: Program<'info, System>,
+}
+
+// ============================================
+// EVENTS
+// ============================================
+
+#[event]
+pub struct TokenRegistered {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub impersonation_risk: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenUpdated {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenAuthorityTransferred {
+    pub mint: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub from_page: Option<u32>,
+    pub to_page: u32,
+}
+
+#[event]
+pub struct TokenDeregistered {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub authority_index_page: Option<u32>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SymbolClaimed {
+    pub symbol: String,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct SymbolReleased {
+    pub symbol: String,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct TokenSymbolChanged {
+    pub mint: Pubkey,
+    pub previous_symbol: String,
+    pub symbol: String,
+    pub impersonation_risk: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingReviewed {
+    pub mint: Pubkey,
+    pub status: ListingStatus,
+    pub reason: ListingReason,
+    pub curator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RolesInitialized {
+    pub super_admin: Pubkey,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub member: Pubkey,
+    pub role: Role,
+    pub granted_by: Pubkey,
+}
 
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct RoleRevoked {
+    pub member: Pubkey,
+    pub role: Role,
+    pub revoked_by: Pubkey,
+}
 
-    #[account(mut)]
-    pub stake_vault: Account<'info, TokenAccount>,
+#[event]
+pub struct ProtocolConfigInitialized {
+    pub authority: Pubkey,
+}
 
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct PauseUpdated {
+    pub subsystem: Subsystem,
+    pub paused: bool,
+    pub pauser: Pubkey,
 }
 
-// ============================================
-// EVENTS
-// ============================================
+#[event]
+pub struct AdminActionQueued {
+    pub action_id: u64,
+    pub change: ParameterChange,
+    pub proposer: Pubkey,
+    pub eta: i64,
+    pub expires_at: i64,
+}
 
 #[event]
-pub struct TokenRegistered {
-    pub mint: Pubkey,
-    pub authority: Pubkey,
-    pub name: String,
+pub struct AdminActionExecuted {
+    pub action_id: u64,
+    pub change: ParameterChange,
+    pub executor: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct TokenUpdated {
-    pub mint: Pubkey,
-    pub authority: Pubkey,
-    pub name: String,
+pub struct AdminActionCancelled {
+    pub action_id: u64,
+    pub change: ParameterChange,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub change: ParameterChange,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub support: bool,
+    pub weight: u64,
+}
+
+#[event]
+pub struct ProposalFinalized {
+    pub proposal_id: u64,
+    pub status: ProposalStatus,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub action_id: Option<u64>,
     pub timestamp: i64,
 }
 
@@ -799,6 +6841,27 @@ pub struct RewardsDistributed {
     pub total_developers: u64,
 }
 
+#[event]
+pub struct AuthorityHandoverProposed {
+    pub target: Pubkey,
+    pub current_authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityHandoverAccepted {
+    pub target: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityHandoverCancelled {
+    pub target: Pubkey,
+    pub current_authority: Pubkey,
+    pub cancelled_authority: Pubkey,
+}
+
 #[event]
 pub struct ProfileCreated {
     pub wallet: Pubkey,
@@ -824,32 +6887,227 @@ pub struct DeveloperRegistered {
     pub total_developers: u64,
 }
 
+#[event]
+pub struct ReceiptMintInitialized {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+}
+
 #[event]
 pub struct Staked {
     pub user: Pubkey,
     pub project_mint: Pubkey,
     pub amount: u64,
     pub total_stakes: u64,
+    pub unique_stakers: u64,
+    pub total_staked: u64,
+    pub lockup: LockupTier,
+    pub unlock_at: i64,
 }
 
 #[event]
-pub struct ProjectVerified {
+pub struct VerificationLevelChanged {
     pub project_mint: Pubkey,
-    pub total_stakes: u64,
+    pub previous_level: VerificationLevel,
+    pub new_level: VerificationLevel,
+    pub unique_stakers: u64,
+    pub total_staked: u64,
+    pub risk_score: u16,
+}
+
+#[event]
+pub struct ProjectRewardsFunded {
+    pub project_mint: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub reward_per_share: u128,
+}
+
+#[event]
+pub struct StakeRewardsClaimed {
+    pub user: Pubkey,
+    pub project_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProjectRewardVaultCreated {
+    pub project_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub stream_index: u8,
+}
+
+#[event]
+pub struct SponsoredRewardsFunded {
+    pub project_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub rate: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct SponsoredRewardsClaimed {
+    pub user: Pubkey,
+    pub project_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct UnallocatedRewardsReclaimed {
+    pub project_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProjectFlagged {
+    pub project_mint: Pubkey,
+    pub moderator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProjectFlagCleared {
+    pub project_mint: Pubkey,
+    pub moderator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFundInitialized {
+    pub token_account: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct InsuranceFundDisbursed {
+    pub recipient: Pubkey,
+    pub treasurer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProjectSlashed {
+    pub project_mint: Pubkey,
+    pub moderator: Pubkey,
+    pub rate_bps: u16,
+    pub slashed_amount: u64,
+    pub challenger_rewards: u64,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChallengeStaked {
+    pub challenger: Pubkey,
+    pub project_mint: Pubkey,
+    pub amount: u64,
+    pub total_challenged: u64,
+}
+
+#[event]
+pub struct ChallengeWithdrawn {
+    pub challenger: Pubkey,
+    pub project_mint: Pubkey,
+    pub amount: u64,
+    pub payout: u64,
+    pub penalty: u64,
+    pub forfeited: u64,
+}
+
+#[event]
+pub struct RiskAttested {
+    pub mint: Pubkey,
+    pub attester: Pubkey,
+    pub risk_score: u16,
+    pub expires_at: i64,
 }
 
 #[event]
 pub struct UnstakeRequested {
     pub user: Pubkey,
     pub project_mint: Pubkey,
+    pub amount: u64,
     pub cooldown_ends: i64,
 }
 
+#[event]
+pub struct UnstakeCancelled {
+    pub user: Pubkey,
+    pub project_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct LockupExpired {
+    pub user: Pubkey,
+    pub project_mint: Pubkey,
+    pub lockup: LockupTier,
+    pub unlock_at: i64,
+}
+
+#[event]
+pub struct Restaked {
+    pub user: Pubkey,
+    pub from_project_mint: Pubkey,
+    pub to_project_mint: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct ReceiptsRedeemed {
+    pub holder: Pubkey,
+    pub owner: Pubkey,
+    pub project_mint: Pubkey,
+    pub receipts: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct UserStakeClosed {
+    pub user: Pubkey,
+    pub project_mint: Pubkey,
+}
+
 #[event]
 pub struct Unstaked {
     pub user: Pubkey,
     pub project_mint: Pubkey,
     pub amount: u64,
+    pub remaining: u64,
+    pub penalty: u64,
+    pub receipts_burned: u64,
+}
+
+#[event]
+pub struct ScamReportFiled {
+    pub reporter: Pubkey,
+    pub mint: Pubkey,
+    pub evidence_hash: String,
+    pub bond_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScamReportResolved {
+    pub reporter: Pubkey,
+    pub mint: Pubkey,
+    pub upheld: bool,
+    pub bond_amount: u64,
+    pub bounty: u64,
+    pub resolver: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub payer: Pubkey,
 }
 
 // ============================================
@@ -880,10 +7138,787 @@ pub enum ChainProofError {
     UnstakeAlreadyRequested,
     #[msg("Unstake not requested yet")]
     UnstakeNotRequested,
-    #[msg("Cooldown period not complete (48 hours required)")]
+    #[msg("Cooldown period not complete")]
     CooldownNotComplete,
     #[msg("Distribution too early (must wait for interval)")]
     DistributionTooEarly,
     #[msg("Insufficient pool balance")]
     InsufficientPoolBalance,
+    #[msg("Evidence hash too long (max 100 characters)")]
+    EvidenceHashTooLong,
+    #[msg("Evidence hash is required")]
+    EvidenceHashRequired,
+    #[msg("Scam report already resolved")]
+    ReportAlreadyResolved,
+    #[msg("Invalid token account")]
+    InvalidTokenAccount,
+    #[msg("Symbol must contain at least one letter or digit")]
+    InvalidSymbol,
+    #[msg("Use change_token_symbol to move a token entry to a different symbol")]
+    SymbolChangeNotAllowed,
+    #[msg("New symbol normalizes to the current one")]
+    SymbolUnchanged,
+    #[msg("Project is not verified")]
+    ProjectNotVerified,
+    #[msg("Token entry is flagged as a possible impersonation")]
+    ImpersonationRisk,
+    #[msg("Symbol already claimed by a verified project")]
+    SymbolAlreadyClaimed,
+    #[msg("Symbol is not claimed by this mint")]
+    SymbolNotClaimed,
+    #[msg("Symbol holder is still verified and approved")]
+    SymbolClaimStillValid,
+    #[msg("Index page account is required for this entry")]
+    MissingIndexPage,
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+    #[msg("Listing is not approved")]
+    ListingNotApproved,
+    #[msg("Listing already approved")]
+    ListingAlreadyApproved,
+    #[msg("A reason code is required to reject a listing")]
+    ListingReasonRequired,
+    #[msg("Signer is missing the required protocol role")]
+    MissingRole,
+    #[msg("Role already granted")]
+    RoleAlreadyGranted,
+    #[msg("Role not granted")]
+    RoleNotGranted,
+    #[msg("Too many role members")]
+    TooManyRoleMembers,
+    #[msg("Can't revoke the last super-admin")]
+    LastSuperAdmin,
+    #[msg("Protocol subsystem is paused")]
+    ProtocolPaused,
+    #[msg("Invalid parameter value")]
+    InvalidParameter,
+    #[msg("Timelock has not elapsed yet")]
+    TimelockNotElapsed,
+    #[msg("Admin action expired without being executed")]
+    AdminActionExpired,
+    #[msg("No authority handover pending")]
+    NoPendingAuthority,
+    #[msg("Not enough stake to create a proposal")]
+    InsufficientProposalStake,
+    #[msg("Proposal is not active")]
+    ProposalNotActive,
+    #[msg("Voting period has ended")]
+    VotingClosed,
+    #[msg("Voting period has not ended yet")]
+    VotingNotEnded,
+    #[msg("No voting power at the proposal snapshot")]
+    NoVotingPower,
+    #[msg("Invalid stake account")]
+    InvalidStakeAccount,
+    #[msg("Duplicate stake account")]
+    DuplicateStakeAccount,
+    #[msg("Risk score out of range (0-100)")]
+    InvalidRiskScore,
+    #[msg("Invalid attestation validity period (max 30 days)")]
+    InvalidAttestationTtl,
+    #[msg("Unstake amount must be between 1 and the staked amount")]
+    InvalidUnstakeAmount,
+    #[msg("Stake position still holds tokens or unclaimed rewards")]
+    StakeNotEmpty,
+    #[msg("Invalid reward amount")]
+    InvalidRewardAmount,
+    #[msg("No rewards to claim")]
+    NoRewardsToClaim,
+    #[msg("Project already has the maximum number of reward streams")]
+    TooManyRewardStreams,
+    #[msg("Invalid reward schedule")]
+    InvalidRewardSchedule,
+    #[msg("Current reward schedule has not ended")]
+    RewardScheduleActive,
+    #[msg("Project has been flagged or slashed")]
+    ProjectNotActive,
+    #[msg("Project is flagged pending review")]
+    ProjectFlagged,
+    #[msg("Project is not flagged")]
+    ProjectNotFlagged,
+    #[msg("Challenge vault is required when the project has challengers")]
+    MissingChallengeVault,
+    #[msg("Challenge dispute window has not ended")]
+    DisputeWindowOpen,
+    #[msg("Stake is still locked")]
+    StakeLocked,
+    #[msg("Source and destination project must differ")]
+    SameProject,
+    #[msg("Batch entries don't match the accounts provided")]
+    InvalidBatch,
+    #[msg("Account is not in a legacy layout")]
+    NotALegacyAccount,
+    #[msg("Token entry still uses the legacy layout; run migrate_account first")]
+    AccountNotMigrated,
+    #[msg("Stake position belongs to a different project")]
+    ProjectMismatch,
+    #[msg("A passed proposal needs the next pending action account")]
+    MissingPendingAction,
+    #[msg("Defeated proposals don't queue an action")]
+    UnexpectedPendingAction,
+    #[msg("Risk attestation account is invalid")]
+    InvalidAttestationAccount,
+    #[msg("Insurance fund is required when slashes go to it")]
+    MissingInsuranceFund,
+    #[msg("No lockup has run out on this position")]
+    LockupNotExpired,
+    #[msg("Destination is locked; restake with at least its lockup tier")]
+    LockupRequired,
+    #[msg("Position holds fewer receipts or points than its stake accounts for")]
+    AccountingUnderflow,
+    #[msg("Receipts don't cover any of this position's stake")]
+    InvalidRedemption,
+}
+
+// ============================================
+// TESTS
+// ============================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position() -> UserStake {
+        UserStake {
+            user: Pubkey::new_unique(),
+            project_mint: Pubkey::new_unique(),
+            amount: 0,
+            staked_at: 0,
+            unstake_requested_at: None,
+            bump: 255,
+            pending_unstake_amount: 0,
+            reward_debt: 0,
+            pending_rewards: 0,
+            sponsored_rewards: Default::default(),
+            slash_applied: false,
+            lockup: LockupTier::None,
+            unlock_at: 0,
+            receipt_balance: 0,
+            active_history: Default::default(),
+            unstake_available_at: 0,
+            reward_points: 0,
+        }
+    }
+
+    fn config() -> ProtocolConfig {
+        ProtocolConfig {
+            paused: 0,
+            admin_timelock: DEFAULT_ADMIN_TIMELOCK,
+            next_action_id: 0,
+            next_proposal_id: 0,
+            verification_tiers: DEFAULT_VERIFICATION_TIERS,
+            max_verified_risk_score: DEFAULT_MAX_VERIFIED_RISK_SCORE,
+            unstake_cooldown: DEFAULT_UNSTAKE_COOLDOWN,
+            slash_rate_bps: DEFAULT_SLASH_RATE_BPS,
+            challenge_dispute_window: DEFAULT_CHALLENGE_DISPUTE_WINDOW,
+            challenge_penalty_bps: DEFAULT_CHALLENGE_PENALTY_BPS,
+            challenge_min_age: DEFAULT_CHALLENGE_MIN_AGE,
+            emergency_unstake_penalty_bps: DEFAULT_EMERGENCY_UNSTAKE_PENALTY_BPS,
+            slash_destination: SlashDestination::RewardPool,
+            bump: 255,
+        }
+    }
+
+    fn project() -> ProjectStakes {
+        ProjectStakes {
+            project_mint: Pubkey::new_unique(),
+            total_stakes: 0,
+            verification_level: VerificationLevel::None,
+            bump: 255,
+            unique_stakers: 0,
+            total_staked: 0,
+            total_pending_unstake: 0,
+            reward_per_share: 0,
+            undistributed_rewards: 0,
+            status: ProjectStatus::Active,
+            flagged_at: 0,
+            flag_cleared_at: 0,
+            slash_rate_bps: 0,
+            total_reward_weight: 0,
+            total_challenged: 0,
+            unique_challengers: 0,
+            challenger_rewards: 0,
+            reward_streams: Vec::new(),
+        }
+    }
+
+    fn attestation(risk_score: u16, expires_at: i64) -> RiskAttestation {
+        RiskAttestation {
+            mint: Pubkey::new_unique(),
+            attester: Pubkey::new_unique(),
+            risk_score,
+            attested_at: 0,
+            expires_at,
+            bump: 255,
+        }
+    }
+
+    fn profile() -> UserProfile {
+        UserProfile {
+            wallet: Pubkey::new_unique(),
+            username: String::from("staker"),
+            referral_code: None,
+            is_developer: false,
+            total_stakes: 0,
+            reward_points: 0,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    /// A position on `project_stakes` with `amount` staked at `now`.
+    fn staked(
+        project_stakes: &mut ProjectStakes,
+        user_profile: &mut UserProfile,
+        amount: u64,
+        lockup: LockupTier,
+        now: i64,
+    ) -> UserStake {
+        let mut user_stake = position();
+        user_stake.project_mint = project_stakes.project_mint;
+        record_stake(project_stakes, &mut user_stake, user_profile, amount, lockup, now).unwrap();
+        user_stake
+    }
+
+    fn change_active(user_stake: &mut UserStake, amount: u64, now: i64) {
+        let previous_active = user_stake.active_amount();
+        user_stake.amount = amount;
+        user_stake.record_active_change(previous_active, now);
+    }
+
+    #[test]
+    fn stakes_count_unique_stakers_and_amounts() {
+        let config = config();
+        let attested = attestation(30, 1_000);
+        let mut project_stakes = project();
+        let mut alice = profile();
+        let mut user_stake = staked(&mut project_stakes, &mut alice, 1, LockupTier::None, 10);
+
+        // Topping up one position many times doesn't look like many stakers
+        for _ in 0..20 {
+            record_stake(&mut project_stakes, &mut user_stake, &mut alice, 1_000_000_000, LockupTier::None, 20)
+                .unwrap();
+        }
+        update_verification_level(&mut project_stakes, &config, Some(&attested), 20);
+        assert_eq!(project_stakes.unique_stakers, 1);
+        assert_eq!(project_stakes.total_stakes, 1);
+        assert_eq!(alice.total_stakes, 1);
+        assert!(project_stakes.verification_level == VerificationLevel::None);
+
+        let mut bob = profile();
+        staked(&mut project_stakes, &mut bob, 500, LockupTier::None, 30);
+        assert_eq!(project_stakes.unique_stakers, 2);
+        assert_eq!(project_stakes.total_staked, 20_000_000_001 + 500);
+        assert_eq!(bob.total_stakes, 1);
+
+        // Only active projects take new stake
+        project_stakes.status = ProjectStatus::Flagged;
+        assert!(record_stake(&mut project_stakes, &mut user_stake, &mut alice, 1, LockupTier::None, 40).is_err());
+    }
+
+    #[test]
+    fn partial_unstake_keeps_the_rest_staked() {
+        let mut project_stakes = project();
+        let mut alice = profile();
+        let mut user_stake = staked(&mut project_stakes, &mut alice, 100, LockupTier::None, 10);
+        user_stake.receipt_balance = 100;
+
+        open_unstake_request(&mut user_stake, &mut project_stakes, 40, DEFAULT_UNSTAKE_COOLDOWN, 20).unwrap();
+        assert_eq!(user_stake.active_amount(), 60);
+        assert_eq!(user_stake.unstake_available_at, 20 + DEFAULT_UNSTAKE_COOLDOWN);
+        assert_eq!(project_stakes.total_staked, 60);
+        assert_eq!(project_stakes.unique_stakers, 1);
+
+        // One request at a time, for no more than the position
+        assert!(open_unstake_request(&mut user_stake, &mut project_stakes, 10, 0, 30).is_err());
+
+        let withdrawal = settle_withdrawal(&mut user_stake, &mut project_stakes, &mut alice, 0).unwrap();
+        assert_eq!(withdrawal.amount, 40);
+        assert_eq!(withdrawal.remaining, 60);
+        assert_eq!(withdrawal.receipts_burned, 40);
+        assert_eq!(user_stake.amount, 60);
+        assert_eq!(user_stake.reward_points, 60);
+        assert_eq!(alice.reward_points, 60);
+        assert_eq!(alice.total_stakes, 1);
+        assert!(user_stake.unstake_requested_at.is_none());
+
+        assert!(open_unstake_request(&mut user_stake, &mut project_stakes, 61, 0, 40).is_err());
+    }
+
+    #[test]
+    fn pending_unstakes_stop_counting_until_cancelled() {
+        let config = config();
+        let attested = attestation(30, 1_000);
+        let mut project_stakes = project();
+        let mut stakes: Vec<UserStake> = (0..10)
+            .map(|_| staked(&mut project_stakes, &mut profile(), 100_000_000, LockupTier::None, 10))
+            .collect();
+        update_verification_level(&mut project_stakes, &config, Some(&attested), 10);
+        assert!(project_stakes.verification_level == VerificationLevel::Bronze);
+
+        // A requested unstake drops the project below Bronze before anything is withdrawn
+        open_unstake_request(&mut stakes[0], &mut project_stakes, 100_000_000, 0, 20).unwrap();
+        update_verification_level(&mut project_stakes, &config, Some(&attested), 20);
+        assert!(project_stakes.verification_level == VerificationLevel::None);
+        assert_eq!(project_stakes.unique_stakers, 9);
+
+        // Cancelling puts the position back and the level with it
+        assert_eq!(cancel_unstake_request(&mut stakes[0], &mut project_stakes, 30).unwrap(), 100_000_000);
+        update_verification_level(&mut project_stakes, &config, Some(&attested), 30);
+        assert!(project_stakes.verification_level == VerificationLevel::Bronze);
+        assert_eq!(project_stakes.unique_stakers, 10);
+        assert_eq!(stakes[0].active_amount(), 100_000_000);
+        assert!(cancel_unstake_request(&mut stakes[0], &mut project_stakes, 40).is_err());
+    }
+
+    #[test]
+    fn full_withdrawal_empties_the_position() {
+        let mut project_stakes = project();
+        let mut alice = profile();
+        let mut user_stake = staked(&mut project_stakes, &mut alice, 100, LockupTier::None, 10);
+        project_stakes.distribute_rewards(50);
+
+        open_unstake_request(&mut user_stake, &mut project_stakes, 100, 0, 20).unwrap();
+        let withdrawal = settle_withdrawal(&mut user_stake, &mut project_stakes, &mut alice, 0).unwrap();
+        assert_eq!(withdrawal.remaining, 0);
+        assert_eq!(project_stakes.total_stakes, 0);
+        assert_eq!(project_stakes.unique_stakers, 0);
+        assert_eq!(alice.total_stakes, 0);
+        assert_eq!(alice.reward_points, 0);
+
+        // Left open until the rewards it earned are claimed
+        assert!(user_stake.has_unclaimed_rewards());
+        user_stake.pending_rewards = 0;
+        assert!(!user_stake.has_unclaimed_rewards());
+    }
+
+    #[test]
+    fn emergency_unstake_opens_its_own_request() {
+        let mut project_stakes = project();
+        let mut alice = profile();
+        let mut user_stake = staked(&mut project_stakes, &mut alice, 100, LockupTier::None, 10);
+
+        open_emergency_request(&mut user_stake, &mut project_stakes, 40, 20).unwrap();
+        assert_eq!(user_stake.pending_unstake_amount, 40);
+        assert_eq!(user_stake.unstake_available_at, 20);
+        let withdrawal = settle_withdrawal(&mut user_stake, &mut project_stakes, &mut alice, 1000).unwrap();
+        assert_eq!(withdrawal.amount, 40);
+        assert_eq!(withdrawal.penalty, 4);
+        assert_eq!(project_stakes.total_staked, 60);
+
+        // An open request is withdrawn as it stands
+        open_unstake_request(&mut user_stake, &mut project_stakes, 30, 1000, 30).unwrap();
+        assert!(open_emergency_request(&mut user_stake, &mut project_stakes, 60, 40).is_err());
+        open_emergency_request(&mut user_stake, &mut project_stakes, 30, 40).unwrap();
+        assert_eq!(settle_withdrawal(&mut user_stake, &mut project_stakes, &mut alice, 1000).unwrap().amount, 30);
+        assert_eq!(user_stake.amount, 30);
+    }
+
+    #[test]
+    fn rewards_split_by_weight_and_wait_for_stakers() {
+        let mut project_stakes = project();
+        project_stakes.distribute_rewards(30);
+        assert_eq!(project_stakes.undistributed_rewards, 30);
+
+        let mut alice = staked(&mut project_stakes, &mut profile(), 100, LockupTier::None, 10);
+        let mut bob = staked(&mut project_stakes, &mut profile(), 200, LockupTier::None, 10);
+
+        // Funding held back while nothing was staked goes out with the next deposit
+        project_stakes.distribute_rewards(60);
+        assert_eq!(project_stakes.undistributed_rewards, 0);
+
+        alice.settle_rewards(&project_stakes);
+        bob.settle_rewards(&project_stakes);
+        assert_eq!(alice.pending_rewards, 30);
+        assert_eq!(bob.pending_rewards, 60);
+
+        // Settling again pays nothing twice, and late stake only earns from then on
+        alice.settle_rewards(&project_stakes);
+        let mut carol = staked(&mut project_stakes, &mut profile(), 300, LockupTier::None, 20);
+        carol.settle_rewards(&project_stakes);
+        assert_eq!(alice.pending_rewards, 30);
+        assert_eq!(carol.pending_rewards, 0);
+    }
+
+    #[test]
+    fn pending_unstakes_earn_nothing() {
+        let mut project_stakes = project();
+        let mut alice = staked(&mut project_stakes, &mut profile(), 100, LockupTier::None, 10);
+        let mut bob = staked(&mut project_stakes, &mut profile(), 100, LockupTier::None, 10);
+        open_unstake_request(&mut alice, &mut project_stakes, 100, 0, 20).unwrap();
+
+        project_stakes.distribute_rewards(50);
+        alice.settle_rewards(&project_stakes);
+        bob.settle_rewards(&project_stakes);
+        assert_eq!(alice.pending_rewards, 0);
+        assert_eq!(bob.pending_rewards, 50);
+    }
+
+    fn stream(rate: u64, start_time: i64, end_time: i64) -> RewardStream {
+        RewardStream {
+            reward_mint: Pubkey::new_unique(),
+            rate,
+            start_time,
+            end_time,
+            last_update: start_time,
+            reward_per_share: 0,
+            unallocated: 0,
+        }
+    }
+
+    #[test]
+    fn stream_emissions_follow_the_schedule() {
+        let mut project_stakes = project();
+        project_stakes.reward_streams.push(stream(10, 0, 100));
+
+        // Nobody staked for the first 40 seconds, so that much is left for the project
+        let mut alice = staked(&mut project_stakes, &mut profile(), 100, LockupTier::None, 40);
+        assert_eq!(project_stakes.reward_streams[0].unallocated, 400);
+
+        let mut bob = staked(&mut project_stakes, &mut profile(), 300, LockupTier::None, 60);
+        project_stakes.accrue_reward_streams(500);
+        alice.settle_rewards(&project_stakes);
+        bob.settle_rewards(&project_stakes);
+
+        // Alice alone for 20s, then a quarter of the last 40s; nothing after end_time
+        assert_eq!(alice.sponsored_rewards[0].pending_rewards, 200 + 100);
+        assert_eq!(bob.sponsored_rewards[0].pending_rewards, 300);
+        assert_eq!(project_stakes.reward_streams[0].unallocated, 400);
+    }
+
+    #[test]
+    fn claims_pay_the_locked_weight_until_release() {
+        let mut project_stakes = project();
+        project_stakes.reward_streams.push(stream(9, 0, 100_000_000));
+        let mut alice = staked(&mut project_stakes, &mut profile(), 100, LockupTier::Days30, 0);
+        let mut bob = staked(&mut project_stakes, &mut profile(), 100, LockupTier::None, 0);
+        assert_eq!(project_stakes.total_reward_weight, 225);
+        assert!(take_stake_rewards(&mut alice, &mut project_stakes, 10).is_err());
+
+        let unlock = alice.unlock_at;
+        project_stakes.distribute_rewards(450);
+        assert_eq!(take_stake_rewards(&mut alice, &mut project_stakes, unlock).unwrap(), 250);
+        assert!(alice.lockup == LockupTier::None);
+        assert_eq!(project_stakes.total_reward_weight, 200);
+
+        // The whole locked period streamed at 125:100, not at the released weight
+        bob.settle_rewards(&project_stakes);
+        assert_eq!(alice.sponsored_rewards[0].pending_rewards, 5 * unlock as u64);
+        assert_eq!(bob.sponsored_rewards[0].pending_rewards, 4 * unlock as u64);
+        assert_eq!(bob.pending_rewards, 200);
+    }
+
+    #[test]
+    fn flagged_projects_set_emissions_aside() {
+        let mut project_stakes = project();
+        project_stakes.reward_streams.push(stream(10, 0, 100));
+        let mut alice = staked(&mut project_stakes, &mut profile(), 100, LockupTier::None, 0);
+
+        project_stakes.accrue_reward_streams(30);
+        project_stakes.status = ProjectStatus::Flagged;
+        project_stakes.accrue_reward_streams(60);
+        alice.settle_rewards(&project_stakes);
+        assert_eq!(alice.sponsored_rewards[0].pending_rewards, 300);
+        assert_eq!(project_stakes.reward_streams[0].unallocated, 300);
+    }
+
+    #[test]
+    fn verification_level_follows_tiers_and_flagging() {
+        let config = config();
+        let attested = attestation(30, 1_000);
+        let mut project_stakes = project();
+        project_stakes.unique_stakers = 25;
+        project_stakes.total_staked = 10_000_000_000;
+
+        update_verification_level(&mut project_stakes, &config, Some(&attested), 10);
+        assert!(project_stakes.verification_level == VerificationLevel::Silver);
+
+        // Stake challenged against the project doesn't count towards a tier
+        project_stakes.total_challenged = 1;
+        update_verification_level(&mut project_stakes, &config, Some(&attested), 10);
+        assert!(project_stakes.verification_level == VerificationLevel::Bronze);
+
+        // Flagging drops every level, clearing the flag recomputes it
+        project_stakes.status = ProjectStatus::Flagged;
+        update_verification_level(&mut project_stakes, &config, Some(&attested), 10);
+        assert!(project_stakes.verification_level == VerificationLevel::None);
+        project_stakes.status = ProjectStatus::Active;
+        update_verification_level(&mut project_stakes, &config, Some(&attested), 10);
+        assert!(project_stakes.verification_level == VerificationLevel::Bronze);
+    }
+
+    #[test]
+    fn verification_needs_a_fresh_passing_attestation() {
+        let config = config();
+        let mut project_stakes = project();
+        project_stakes.unique_stakers = 50;
+        project_stakes.total_staked = 50_000_000_000;
+
+        update_verification_level(&mut project_stakes, &config, Some(&attestation(30, 1_000)), 10);
+        assert!(project_stakes.verification_level == VerificationLevel::Gold);
+
+        // Riskier projects only reach the tiers that allow their score
+        update_verification_level(&mut project_stakes, &config, Some(&attestation(50, 1_000)), 10);
+        assert!(project_stakes.verification_level == VerificationLevel::Silver);
+        update_verification_level(&mut project_stakes, &config, Some(&attestation(80, 1_000)), 10);
+        assert!(project_stakes.verification_level == VerificationLevel::None);
+
+        // An expired or missing attestation counts as the riskiest score
+        update_verification_level(&mut project_stakes, &config, Some(&attestation(30, 1_000)), 10);
+        update_verification_level(&mut project_stakes, &config, Some(&attestation(30, 1_000)), 1_000);
+        assert!(project_stakes.verification_level == VerificationLevel::None);
+        update_verification_level(&mut project_stakes, &config, None, 10);
+        assert!(project_stakes.verification_level == VerificationLevel::None);
+    }
+
+    #[test]
+    fn attestation_counts_as_missing_only_while_empty() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut empty: Vec<u8> = Vec::new();
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut empty, &crate::ID, false, 0);
+        assert!(read_risk_attestation(&info).unwrap().is_none());
+
+        let mut data = Vec::new();
+        attestation(30, 1_000).try_serialize(&mut data).unwrap();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &crate::ID, false, 0);
+        assert_eq!(read_risk_attestation(&info).unwrap().unwrap().risk_score, 30);
+
+        // The same bytes under another owner are rejected rather than ignored
+        let other_owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &other_owner, false, 0);
+        assert!(read_risk_attestation(&info).is_err());
+    }
+
+    #[test]
+    fn voting_snapshot_counts_the_amount_held_at_the_time() {
+        let mut user_stake = position();
+        change_active(&mut user_stake, 100, 10);
+        change_active(&mut user_stake, 1_000, 50);
+        change_active(&mut user_stake, 1_000, 55);
+        change_active(&mut user_stake, 0, 70);
+
+        assert_eq!(user_stake.active_amount_at(5), 0);
+        assert_eq!(user_stake.active_amount_at(20), 100);
+        assert_eq!(user_stake.active_amount_at(60), 1_000);
+        assert_eq!(user_stake.active_amount_at(80), 0);
+    }
+
+    #[test]
+    fn voting_snapshot_survives_a_full_history() {
+        let mut holder = position();
+        change_active(&mut holder, 100, 10);
+        let mut late = position();
+        change_active(&mut late, 100, 20);
+        for i in 1..=20 {
+            change_active(&mut holder, 100 + i, 20 + i as i64);
+            change_active(&mut late, 100 + i, 20 + i as i64);
+        }
+
+        // Still counted after many top-ups, and stake added after the snapshot isn't
+        assert_eq!(holder.active_amount_at(15), 100);
+        assert_eq!(late.active_amount_at(15), 0);
+        assert_eq!(holder.active_amount_at(50), 120);
+    }
+
+    #[test]
+    fn voting_snapshot_is_never_overstated() {
+        let mut user_stake = position();
+        let mut held = vec![(0, 0)];
+        let mut seed: u64 = 7;
+        for now in 1..200 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let amount = (seed >> 33) % 1_000;
+            change_active(&mut user_stake, amount, now);
+            held.push((now, amount));
+
+            for &(at, _) in &held {
+                // The amount held at `at` is the one set before it
+                let actual = held.iter().rev().find(|(t, _)| *t < at).map_or(0, |(_, a)| *a);
+                assert!(user_stake.active_amount_at(at) <= actual);
+            }
+        }
+        assert_eq!(user_stake.active_amount_at(200), user_stake.active_amount());
+    }
+
+    #[test]
+    fn slash_takes_tracked_stake_only() {
+        let mut project_stakes = project();
+        let mut alice = staked(&mut project_stakes, &mut profile(), 100, LockupTier::None, 10);
+        let mut bob = staked(&mut project_stakes, &mut profile(), 300, LockupTier::None, 10);
+        open_unstake_request(&mut bob, &mut project_stakes, 100, 0, 20).unwrap();
+        assert_eq!(project_stakes.total_pending_unstake, 100);
+
+        // Tokens sent straight to the vault are not part of the slash
+        let donated = 50;
+        let mut vault = 400 + donated;
+        project_stakes.status = ProjectStatus::Flagged;
+        let slashed = slash_project_stakes(&mut project_stakes, 1000, false);
+        assert_eq!(slashed, 40);
+        assert_eq!(project_stakes.total_staked, 270);
+        assert_eq!(project_stakes.total_pending_unstake, 90);
+        vault -= slashed;
+
+        // Each position is scaled once and the vault covers every withdrawal
+        vault -= settle_withdrawal(&mut bob, &mut project_stakes, &mut profile(), 0).unwrap().amount;
+        open_unstake_request(&mut alice, &mut project_stakes, 90, 0, 30).unwrap();
+        vault -= settle_withdrawal(&mut alice, &mut project_stakes, &mut profile(), 0).unwrap().amount;
+        open_unstake_request(&mut bob, &mut project_stakes, 180, 0, 30).unwrap();
+        vault -= settle_withdrawal(&mut bob, &mut project_stakes, &mut profile(), 0).unwrap().amount;
+        assert_eq!(vault, donated);
+        assert_eq!(project_stakes.total_staked, 0);
+        assert_eq!(project_stakes.total_pending_unstake, 0);
+    }
+
+    #[test]
+    fn challenges_pay_a_penalty_only_after_a_cleared_flag() {
+        let config = config();
+        let window = config.challenge_dispute_window;
+        let mut project_stakes = project();
+        let challenge = ChallengeStake {
+            challenger: Pubkey::new_unique(),
+            project_mint: project_stakes.project_mint,
+            amount: 1000,
+            staked_at: 100,
+            bump: 255,
+        };
+
+        assert!(challenge_payout(&challenge, &project_stakes, &config, 100 + window - 1).is_err());
+
+        // Never flagged: the full principal comes back
+        let never_flagged = challenge_payout(&challenge, &project_stakes, &config, 100 + window).unwrap();
+        assert_eq!(never_flagged.payout, 1000);
+        assert_eq!(never_flagged.penalty, 0);
+
+        // Flagged and cleared before the window ran out
+        project_stakes.flag_cleared_at = 100 + window - 1;
+        let cleared_early = challenge_payout(&challenge, &project_stakes, &config, 100 + window).unwrap();
+        assert_eq!(cleared_early.penalty, 0);
+
+        // Cleared after the window
+        project_stakes.flag_cleared_at = 100 + window;
+        let cleared = challenge_payout(&challenge, &project_stakes, &config, 100 + window).unwrap();
+        let penalty = 1000 * config.challenge_penalty_bps as u64 / 10000;
+        assert_eq!(cleared.penalty, penalty);
+        assert_eq!(cleared.payout, 1000 - penalty);
+
+        project_stakes.status = ProjectStatus::Flagged;
+        assert!(challenge_payout(&challenge, &project_stakes, &config, 100 + window).is_err());
+    }
+
+    #[test]
+    fn late_challenges_forfeit_their_slash_share() {
+        let config = config();
+        let mut project_stakes = project();
+        project_stakes.status = ProjectStatus::Slashed;
+        project_stakes.flagged_at = 1000;
+        project_stakes.total_challenged = 400;
+        project_stakes.challenger_rewards = 80;
+        let mut challenge = ChallengeStake {
+            challenger: Pubkey::new_unique(),
+            project_mint: project_stakes.project_mint,
+            amount: 100,
+            staked_at: 1000 - config.challenge_min_age,
+            bump: 255,
+        };
+
+        let early = challenge_payout(&challenge, &project_stakes, &config, 2000).unwrap();
+        assert_eq!(early.payout, 120);
+        assert_eq!(early.forfeited, 0);
+
+        challenge.staked_at += 1;
+        let late = challenge_payout(&challenge, &project_stakes, &config, 2000).unwrap();
+        assert_eq!(late.payout, 100);
+        assert_eq!(late.forfeited, 20);
+    }
+
+    #[test]
+    fn restake_moves_a_proportional_share_out() {
+        let mut project_stakes = project();
+        let mut alice = profile();
+        let mut user_stake = staked(&mut project_stakes, &mut alice, 100, LockupTier::Days30, 10);
+        user_stake.receipt_balance = 100;
+        let points = user_stake.reward_points;
+        assert_eq!(alice.reward_points, points);
+
+        let unlock = user_stake.unlock_at;
+        assert!(move_out_of_position(&mut user_stake, &mut project_stakes, &mut alice, 40, unlock - 1).is_err());
+
+        let moved = move_out_of_position(&mut user_stake, &mut project_stakes, &mut alice, 40, unlock).unwrap();
+        assert_eq!(moved.receipts, 40);
+        assert_eq!(moved.remaining, 60);
+        assert_eq!(user_stake.receipt_balance, 60);
+        assert_eq!(user_stake.reward_points, points - points * 4 / 10);
+        assert!(user_stake.lockup == LockupTier::None);
+        assert_eq!(project_stakes.total_staked, 60);
+        assert_eq!(project_stakes.total_reward_weight, 60);
+        assert_eq!(project_stakes.total_stakes, 1);
+
+        assert!(move_out_of_position(&mut user_stake, &mut project_stakes, &mut alice, 61, unlock).is_err());
+        let moved = move_out_of_position(&mut user_stake, &mut project_stakes, &mut alice, 60, unlock).unwrap();
+        assert_eq!(moved.remaining, 0);
+        assert_eq!(user_stake.receipt_balance, 0);
+        assert_eq!(user_stake.reward_points, 0);
+        assert_eq!(project_stakes.total_stakes, 0);
+        assert_eq!(alice.total_stakes, 0);
+        assert_eq!(alice.reward_points, 0);
+    }
+
+    #[test]
+    fn receipts_redeem_at_the_position_rate() {
+        let mut project_stakes = project();
+        let mut alice = profile();
+        let mut bob = profile();
+        let mut owner_stake = staked(&mut project_stakes, &mut alice, 300, LockupTier::None, 10);
+        owner_stake.receipt_balance = 200;
+
+        assert!(redeem_from_position(&mut owner_stake, &mut project_stakes, &mut alice, 201, 20).is_err());
+        let amount = redeem_from_position(&mut owner_stake, &mut project_stakes, &mut alice, 50, 20).unwrap();
+        assert_eq!(amount, 75);
+        assert_eq!(owner_stake.amount, 225);
+        assert_eq!(owner_stake.receipt_balance, 150);
+
+        // The redeemed stake moves into the holder's position without leaving the vault
+        let mut holder_stake = staked(&mut project_stakes, &mut bob, amount, LockupTier::None, 20);
+        assert_eq!(project_stakes.total_staked, 300);
+        assert_eq!(project_stakes.total_stakes, 2);
+        assert_eq!(holder_stake.receipts_for(amount), 0);
+
+        // Rounding never hands out stake the receipts didn't pay for
+        owner_stake.amount = 3;
+        owner_stake.receipt_balance = 2;
+        assert_eq!(redeem_from_position(&mut owner_stake, &mut project_stakes, &mut alice, 1, 30).unwrap(), 1);
+        assert_eq!(owner_stake.receipt_balance, 1);
+        assert_eq!(owner_stake.amount, 2);
+
+        // Pending stake can't be redeemed
+        open_unstake_request(&mut holder_stake, &mut project_stakes, amount, 0, 30).unwrap();
+        holder_stake.receipt_balance = 10;
+        assert!(redeem_from_position(&mut holder_stake, &mut project_stakes, &mut bob, 10, 30).is_err());
+    }
+
+    #[test]
+    fn homoglyph_table_matches_registration_script() {
+        let script = include_str!("scripts/registerToken.js");
+        let start = script.find("const HOMOGLYPHS = {").unwrap();
+        let end = start + script[start..].find("};").unwrap();
+        let quoted: Vec<char> = script[start..end]
+            .split('\'')
+            .skip(1)
+            .step_by(2)
+            .map(|s| s.chars().next().unwrap())
+            .collect();
+        let table: Vec<(char, char)> = quoted.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+
+        for (from, to) in &table {
+            assert_eq!(fold_homoglyph(*from), *to, "script folds {from:?} differently");
+        }
+        for c in (0..0x500).filter_map(char::from_u32) {
+            if fold_homoglyph(c) != c {
+                assert!(table.contains(&(c, fold_homoglyph(c))), "script is missing {c:?}");
+            }
+        }
+    }
 }
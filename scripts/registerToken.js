@@ -3,6 +3,55 @@ import { MUAlgorithm } from './algo.js';
 import { TokenRiskScorer } from './riskscore.js';
 import { JupiterChecker } from './jupiterchecker.js';
 import axios from 'axios';
+import { Connection, PublicKey } from '@solana/web3.js';
+import pkg from '@coral-xyz/anchor';
+const { AnchorProvider, Program, web3 } = pkg;
+import provider from '@coral-xyz/anchor/dist/cjs/provider.js';
+const { Wallet } = provider;
+import bs58 from 'bs58';
+import { readFileSync } from 'fs';
+import { fileURLToPath } from 'url';
+import { dirname, join } from 'path';
+
+const __dirname = dirname(fileURLToPath(import.meta.url));
+const idl = JSON.parse(readFileSync(join(__dirname, '../chainproof_idl.json'), 'utf8'));
+const PROGRAM_ID = new PublicKey(idl.address);
+
+// Mirrors fold_homoglyph in contract.rs; the symbol index PDAs are seeded by the folded symbol.
+// homoglyph_table_matches_registration_script in contract.rs fails if the two tables drift.
+const HOMOGLYPHS = {
+  '0': 'O', 'О': 'O', 'о': 'O', 'Ο': 'O', 'ο': 'O',
+  '1': 'I', 'l': 'I', '|': 'I', '!': 'I', 'І': 'I', 'і': 'I', 'Ι': 'I',
+  '5': 'S', '$': 'S', 'Ѕ': 'S', 'ѕ': 'S',
+  'А': 'A', 'а': 'A', 'Α': 'A',
+  'В': 'B', 'в': 'B', 'Β': 'B',
+  'С': 'C', 'с': 'C', 'Ϲ': 'C', 'ϲ': 'C',
+  'Е': 'E', 'е': 'E', 'Ε': 'E',
+  'Н': 'H', 'н': 'H', 'Η': 'H',
+  'Ј': 'J', 'ј': 'J',
+  'К': 'K', 'к': 'K', 'Κ': 'K', 'κ': 'K',
+  'М': 'M', 'м': 'M', 'Μ': 'M',
+  'Ν': 'N',
+  'Р': 'P', 'р': 'P', 'Ρ': 'P',
+  'Т': 'T', 'т': 'T', 'Τ': 'T', 'τ': 'T',
+  'Х': 'X', 'х': 'X', 'Χ': 'X',
+  'У': 'Y', 'у': 'Y', 'Υ': 'Y',
+  'Ζ': 'Z'
+};
+
+export function normalizeSymbol(symbol) {
+  return [...symbol]
+    .filter(c => !/\s/.test(c) && !['.', '-', '_'].includes(c))
+    .map(c => HOMOGLYPHS[c] ?? c)
+    .map(c => (c >= 'a' && c <= 'z' ? c.toUpperCase() : c))
+    .join('');
+}
+
+function pageSeed(page) {
+  const buf = Buffer.alloc(4);
+  buf.writeUInt32LE(page);
+  return buf;
+}
 
 class TokenRegistryManager {
   constructor(pinataApiKey, pinataSecretApiKey) {
@@ -134,6 +183,55 @@ class TokenRegistryManager {
     }
   }
 
+  /**
+   * Submits register_token for the analyzed mint. The index pages are picked
+   * on-chain, so this only reads each index's open page to derive the accounts.
+   */
+  async registerOnChain(connection, keypair, mintAddress, projectName, symbol, ipfsHash) {
+    const wallet = new Wallet(keypair);
+    const anchorProvider = new AnchorProvider(connection, wallet, { commitment: 'confirmed' });
+    const program = new Program(idl, anchorProvider);
+
+    const mint = new PublicKey(mintAddress);
+    const authority = wallet.publicKey;
+    const normalized = Buffer.from(normalizeSymbol(symbol));
+
+    const [protocolConfig] = PublicKey.findProgramAddressSync([Buffer.from('protocol_config')], PROGRAM_ID);
+    const [tokenEntry] = PublicKey.findProgramAddressSync([Buffer.from('token_entry'), mint.toBuffer()], PROGRAM_ID);
+    const [symbolIndex] = PublicKey.findProgramAddressSync([Buffer.from('symbol_index'), normalized], PROGRAM_ID);
+    const [authorityIndex] = PublicKey.findProgramAddressSync(
+      [Buffer.from('authority_index'), authority.toBuffer()],
+      PROGRAM_ID
+    );
+
+    const symbolHead = await program.account.symbolIndex.fetchNullable(symbolIndex);
+    const authorityHead = await program.account.authorityIndex.fetchNullable(authorityIndex);
+
+    const [symbolIndexPage] = PublicKey.findProgramAddressSync(
+      [Buffer.from('symbol_index_page'), normalized, pageSeed(symbolHead ? symbolHead.openPage : 0)],
+      PROGRAM_ID
+    );
+    const [authorityIndexPage] = PublicKey.findProgramAddressSync(
+      [Buffer.from('authority_index_page'), authority.toBuffer(), pageSeed(authorityHead ? authorityHead.openPage : 0)],
+      PROGRAM_ID
+    );
+
+    return await program.methods
+      .registerToken(projectName, symbol, ipfsHash)
+      .accounts({
+        authority,
+        protocolConfig,
+        mint,
+        tokenEntry,
+        symbolIndex,
+        symbolIndexPage,
+        authorityIndex,
+        authorityIndexPage,
+        systemProgram: web3.SystemProgram.programId
+      })
+      .rpc();
+  }
+
   async saveLocalCopy(tokenData, mintAddress) {
     const fs = await import('fs');
     const filename = `token_data_${mintAddress}_${Date.now()}.json`;
@@ -170,6 +268,21 @@ async function main() {
 
   const registry = new TokenRegistryManager(pinataApiKey, pinataSecretApiKey);
   const result = await registry.registerToken(mintAddress, projectName, socials);
+
+  // Optionally write the entry to the on-chain registry as well
+  const keypairString = process.env.SERVER_WALLET_KEYPAIR;
+  if (result.success && keypairString) {
+    const connection = new Connection(process.env.SOLANA_RPC_URL || 'https://api.devnet.solana.com', 'confirmed');
+    const keypair = web3.Keypair.fromSecretKey(bs58.decode(keypairString));
+    result.signature = await registry.registerOnChain(
+      connection,
+      keypair,
+      mintAddress,
+      projectName,
+      result.tokenData.tokenInfo.symbol,
+      result.ipfsHash
+    );
+  }
   
   console.log(JSON.stringify(result, null, 2));
 }
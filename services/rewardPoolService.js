@@ -87,6 +87,12 @@ class RewardPoolService {
         PROGRAM_ID
       );
 
+      // Get protocol config PDA (deposits are rejected while pool deposits are paused)
+      const [protocolConfigPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('protocol_config')],
+        PROGRAM_ID
+      );
+
       // Get depositor's token account
       const depositorTokenAccount = await getAssociatedTokenAddress(
         STAKE_TOKEN_MINT,
@@ -105,9 +111,10 @@ class RewardPoolService {
         .depositToPool(new BN(amountLamports))
         .accounts({
           depositor: this.wallet.publicKey,
+          protocolConfig: protocolConfigPda,
           rewardPool: rewardPoolPda,
           depositorTokenAccount,
-          poolVault,
+          poolTokenAccount: poolVault,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .rpc();